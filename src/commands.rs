@@ -1,16 +1,33 @@
 use crate::core::{
-    config::{EditorConfig, get_config, reset_config, update_config},
-    editor::open_with_editor,
+    config::{
+        Config, EditorConfig, EditorMode, MarkerRule, ProjectRoot, SkipDirs, default_editors,
+        default_mode,
+        get_config, reset_config, update_config, write_config,
+    },
+    editor::{FileTarget, OpenRequest, effective_mode, open_with_editor, split_command_line},
+    git::{RepoStatus, repo_statuses},
+    matcher::{ProjectMatch, picker_score, rank_projects, unambiguous},
+    pending::{get_pending, queue_pending, remove_pending},
     project::{
-        delete_project, get_projects, path_basename, rename_project, reset_projects, resolve_path,
-        set_project, set_project_validated, try_resolve_existing_dir, write_projects,
+        ConflictStrategy, NameStrategy, ProjectEntry, delete_project, get_index_path, get_projects,
+        normalize_tags, now_secs, path_basename, path_str, record_open, relocate_project, rename_project,
+        reset_projects, resolve_path, set_project, set_project_validated, tag_counts, tag_project,
+        try_resolve_existing_dir, untag_project, update_projects,
     },
 };
 use crate::scanner::{
-    FilterMode, ProjectType, add_projects, detect_project_type, interactive_select_projects,
-    scan_projects, search_directory_by_name,
+    Detection, FilterMode, FoundProject, Framework, Identity, MoveMatch, NameSource, ProjectType,
+    SEARCH_DEPTH, ScanRules, SyncPlan, add_projects, apply_sync, best_match, detect_project,
+    find_projects, Walked, interactive_select_projects, load_index, plan_sync, project_name,
+    rebuild_index, resolve_subdir, scan_projects, scan_rules, search_directory_by_name, select_sync_changes,
+};
+use crate::error::Error;
+use crate::shell::complete::{Candidates, completion_context};
+use crate::shell::{CD_FILE_ENV, InitShell, init_script};
+use crate::ui::{
+    LogType, log, print_git_project_rows, print_project_rows, print_recent_rows, print_tag_counts,
+    print_typed_project_rows, relative_age,
 };
-use crate::ui::{LogType, log, print_project_rows, print_table};
 use clap::{Subcommand, ValueEnum};
 use colored::Colorize;
 use std::collections::HashMap;
@@ -23,12 +40,169 @@ pub enum SortKey {
     Name,
     Path,
     Type,
+    /// Most-used and most-recently-opened first (see `ProjectEntry::frecency`).
+    Frecency,
+}
+
+/// Logs `err` as a `✗` line and exits non-zero: the CLI's answer to a
+/// library error it can't recover from.
+fn exit_with(err: impl std::fmt::Display) -> ! {
+    log(&format!("✗ {}", err), LogType::Error);
+    std::process::exit(1);
+}
+
+/// Logs a failed registry write. Name conflicts get the existing entry's
+/// path and a pointer to `--force`, since that's what the user needs next.
+fn log_write_error(e: &Error) {
+    log(&format!("✗ {}", e), LogType::Error);
+    if let Error::Conflict(name) = e {
+        if let Some(existing) = get_projects().ok().and_then(|p| p.get(name).cloned()) {
+            log(&format!("  registered at {}", existing.path), LogType::Info);
+        }
+        log("Use --force to replace it, or choose a different name", LogType::Info);
+    }
+}
+
+/// Normalizes `--tag` arguments, exiting on an invalid one.
+fn parse_tags(raw: &[String]) -> Vec<String> {
+    normalize_tags(raw).unwrap_or_else(|e| exit_with(e))
+}
+
+/// Drops every project that doesn't carry all of `tags`.
+fn retain_tagged(projects: &mut HashMap<String, ProjectEntry>, tags: &[String]) {
+    if !tags.is_empty() {
+        projects.retain(|_, e| e.has_all_tags(tags));
+    }
+}
+
+fn load_projects() -> HashMap<String, ProjectEntry> {
+    get_projects().unwrap_or_else(|e| exit_with(e))
+}
+
+/// Loads the config, running first-time setup if there isn't one yet.
+fn load_config() -> Config {
+    match get_config() {
+        Ok(config) => config,
+        Err(Error::ConfigMissing(_)) => init_config(),
+        Err(e) => exit_with(e),
+    }
+}
+
+/// Applies `f` to the config on disk under its lock, so concurrent edits
+/// don't lose each other's changes. Runs first-time setup like
+/// [`load_config`] and exits if the config can't be written.
+fn edit_config<T>(f: impl FnOnce(&mut Config) -> T) -> T {
+    load_config();
+    update_config(|config| Ok(f(config)))
+        .unwrap_or_else(|e| exit_with(format!("Failed to update config: {}", e)))
+}
+
+fn resolve_arg(input: &str) -> PathBuf {
+    resolve_path(input).unwrap_or_else(|e| exit_with(e))
+}
+
+/// The configured project roots that exist, resolved, with their configured
+/// settings. Missing ones are warned about and left out.
+fn existing_roots(config: &Config) -> Vec<(PathBuf, &ProjectRoot)> {
+    config
+        .roots
+        .iter()
+        .filter_map(|root| {
+            let path = resolve_arg(&root.path);
+            if path.is_dir() {
+                Some((path, root))
+            } else {
+                log(
+                    &format!("⚠ Skipping project root {}: not a directory", path.display()),
+                    LogType::Warning,
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// First-time setup: prompts for the projects root and default editor, then
+/// writes a fresh `config.json`. Exits if the user cancels, since nothing
+/// that needs a config can proceed without one.
+pub fn init_config() -> Config {
+    use inquire::{Select, Text};
+
+    println!("First time setup!");
+
+    let default_root = dirs::home_dir()
+        .map(|h| h.join("projects").to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let projects_root =
+        match Text::new("Provide a path to the directory that contains all your projects:")
+            .with_default(&default_root)
+            .prompt()
+        {
+            Ok(root) => root,
+            Err(_) => exit_with("Setup cancelled"),
+        };
+
+    let editors = default_editors();
+    let mut editor_names: Vec<&str> = editors.keys().map(|s| s.as_str()).collect();
+    editor_names.sort_unstable();
+
+    let default_editor = match Select::new("Choose your default editor:", editor_names).prompt() {
+        Ok(editor) => editor.to_string(),
+        Err(_) => exit_with("Setup cancelled"),
+    };
+
+    let config = Config::new(projects_root, default_editor);
+    if let Err(e) = write_config(&config) {
+        exit_with(format!("Failed to write config: {}", e));
+    }
+    config
+}
+
+/// A picker option shown as `name → path`. Typing in the picker filters with
+/// the same matcher `vcode <name>` uses, so ranking agrees everywhere.
+#[derive(Debug, Clone)]
+struct Choice {
+    name: String,
+    path: String,
+}
+
+impl std::fmt::Display for Choice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} → {}", self.name, self.path)
+    }
+}
+
+/// inquire `Scorer` for `Choice` pickers; see `matcher::picker_score`.
+fn choice_scorer(input: &str, choice: &Choice, _display: &str, idx: usize) -> Option<i64> {
+    picker_score(input, &choice.name, &choice.path, idx)
+}
+
+/// Shows a filterable single-select over `choices` (kept in the given order
+/// until the user types) and returns the pick, or `None` on cancel.
+fn pick_choice(prompt: &str, choices: Vec<Choice>, page_size: usize) -> Option<Choice> {
+    use inquire::Select;
+    Select::new(prompt, choices)
+        .with_scorer(&choice_scorer)
+        .with_page_size(page_size)
+        .prompt()
+        .ok()
 }
 
-/// Copies `cd <path>` to the system clipboard, prints a `✓ Copied: ...`
-/// notice on stderr, and exits the process. Diverges either way so callers
-/// can use it as the terminal action of a `--cd` branch.
-fn copy_cd_to_clipboard_or_exit(path: &str) -> ! {
+/// Hands `path` to whoever asked for `--cd`, and exits the process. Under
+/// the `vcode init` wrapper that's the file named by `VCODE_CD_FILE`, which
+/// the wrapper then `cd`s to; otherwise `cd <path>` is copied to the system
+/// clipboard with a `✓ Copied: ...` notice on stderr. Diverges either way so
+/// callers can use it as the terminal action of a `--cd` branch.
+fn emit_cd_and_exit(path: &str) -> ! {
+    if let Some(file) = std::env::var_os(CD_FILE_ENV) {
+        match std::fs::write(&file, path) {
+            Ok(()) => std::process::exit(0),
+            Err(e) => {
+                eprintln!("vcode: could not hand directory to the shell: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
     match crate::core::clipboard::copy_cd_command(path) {
         Ok(copied) => {
             eprintln!("{}", format!("✓ Copied: {}", copied).green().bold());
@@ -46,7 +220,8 @@ fn copy_cd_to_clipboard_or_exit(path: &str) -> ! {
 pub enum ConfigAction {
     /// Show current configuration
     Show,
-    /// Set a configuration value (key: editor, projects-root)
+    /// Set a configuration value (key: editor, projects-root, on-conflict, naming,
+    /// terminal, walk-max-entries, walk-timeout)
     Set {
         /// Key to set
         key: String,
@@ -62,22 +237,121 @@ pub enum ConfigAction {
         /// Editor name to remove
         name: String,
     },
+    /// Extra project markers for types vcode doesn't detect on its own
+    Marker {
+        #[command(subcommand)]
+        action: MarkerAction,
+    },
+    /// Directories scans skip (node_modules, target, ...)
+    Skip {
+        #[command(subcommand)]
+        action: SkipAction,
+    },
+    /// Directories `vcode scan` and `search --fs` look in
+    Root {
+        #[command(subcommand)]
+        action: RootAction,
+    },
     /// Interactive configuration wizard
     Edit,
     /// Reset configuration to defaults
     Reset,
 }
 
-pub fn handle_add(name: String, path: Option<String>, find: bool) {
+/// How `vcode list` orders its projects and which columns it adds.
+#[derive(clap::Args, Debug, Clone, Copy)]
+pub struct ListView {
+    /// Sort projects by this key [default: name, or frecency with -i]
+    #[arg(short, long, value_enum)]
+    pub sort: Option<SortKey>,
+    /// Add branch, changes, upstream and last commit columns for git repositories
+    #[arg(short, long, conflicts_with_all = ["interactive", "tags"])]
+    pub git: bool,
+}
+
+/// Flags controlling what `vcode scan` walks into.
+#[derive(clap::Args, Debug, Clone, Copy)]
+pub struct WalkOptions {
+    /// Don't list members of Cargo/npm/pnpm/Go workspaces as separate projects
+    #[arg(long)]
+    pub no_members: bool,
+    /// Enter directories excluded by .gitignore, .ignore, .vcodeignore or the global ignore file
+    #[arg(long)]
+    pub no_ignore: bool,
+}
+
+/// How `vcode scan` registers what it finds.
+#[derive(clap::Args, Debug, Clone)]
+pub struct RegisterOptions {
+    /// What to do when a discovered name is already taken (defaults to config `on_conflict`)
+    #[arg(long, value_enum)]
+    pub on_conflict: Option<ConflictStrategy>,
+    /// How to name discovered projects (defaults to config `naming`)
+    #[arg(long, value_enum)]
+    pub naming: Option<NameStrategy>,
+    /// Tag every discovered project (repeat for several tags)
+    #[arg(short = 't', long = "tag")]
+    pub tag: Vec<String>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum MarkerAction {
+    /// List the configured marker rules
+    List,
+    /// Detect directories containing PATTERN as projects of TYPE
+    Add {
+        /// File name (mix.exs), nested path (src-tauri/tauri.conf.json) or glob (*.tf)
+        pattern: String,
+        /// Type name to show, e.g. Elixir; a built-in name adds to that type
+        #[arg(value_name = "TYPE")]
+        type_name: String,
+    },
+    /// Remove the rule for PATTERN
+    Remove { pattern: String },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum SkipAction {
+    /// Show the configured additions and removals
+    List,
+    /// Skip directories with this name
+    Add { name: String },
+    /// Stop skipping directories with this name, including built-in ones
+    Remove { name: String },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum RootAction {
+    /// List the project roots in order
+    List,
+    /// Add a project root, or change the settings of an existing one
+    Add {
+        path: String,
+        /// Levels below the root where projects live
+        #[arg(short, long, default_value = "1")]
+        depth: u32,
+        /// Tag projects found under this root (repeat for several tags)
+        #[arg(short = 't', long = "tag")]
+        tag: Vec<String>,
+        /// Prepend this to the names of projects found under this root
+        #[arg(long)]
+        prefix: Option<String>,
+    },
+    /// Remove a project root (registered projects are kept)
+    Remove { path: String },
+}
+
+pub fn handle_add(name: String, path: Option<String>, find: bool, force: bool, naming: Option<NameStrategy>) {
     if find {
-        handle_find_add(name);
+        handle_find_add(name, force);
         return;
     }
 
-    let (project_name, raw_path) = match path {
-        Some(p) => (name, p),
-        None => match infer_name_from_path_arg(&name) {
-            Some(inferred) => (inferred, name),
+    let naming = naming.unwrap_or_else(configured_naming);
+    let (project_name, raw_path, source) = match path {
+        Some(p) => (name, p, NameSource::Dirname),
+        None => match infer_name_from_path_arg(&name, naming) {
+            Some((inferred, source)) => (inferred, name, source),
             None => {
                 log("✗ Path is required when not using --find", LogType::Error);
                 log("Usage: vcode add <name> <path>", LogType::Info);
@@ -91,41 +365,58 @@ pub fn handle_add(name: String, path: Option<String>, find: bool) {
         },
     };
 
-    // Warn (but don't block) when overwriting an existing project. The check
-    // happens before write to give the user a heads-up at the right moment.
-    let existing = get_projects();
-    if let Some(old_path) = existing.get(&project_name) {
-        let resolved_str = resolve_path(&raw_path).to_string_lossy().into_owned();
-        if old_path != &resolved_str {
+    // With --force, say what's being replaced before it's gone.
+    if force && let Some(old) = load_projects().get(&project_name) {
+        let resolved_str = resolve_arg(&raw_path).to_string_lossy().into_owned();
+        if old.path != resolved_str {
             log(
-                &format!("⚠ Overwriting '{}' (was: {})", project_name, old_path),
+                &format!("⚠ Overwriting '{}' (was: {})", project_name, old.path),
                 LogType::Warning,
             );
         }
     }
 
-    match set_project_validated(&project_name, &raw_path) {
+    match set_project_validated(&project_name, &raw_path, force) {
         Ok(resolved) => log(
-            &format!("✓ Added project '{}' → {}", project_name, resolved.display()),
+            &format!(
+                "✓ Added project '{}' → {}{}",
+                project_name,
+                resolved.display(),
+                name_note(source)
+            ),
             LogType::Success,
         ),
         Err(e) => {
-            log(&format!("✗ {}", e), LogType::Error);
+            log_write_error(&e);
             std::process::exit(1);
         }
     }
 }
 
 /// When the user passes only one positional argument to `add`, decide whether
-/// it should be treated as a path (with the name inferred from its basename).
-/// Returns the inferred name if so, or None if the argument looks like a bare
-/// project name.
-fn infer_name_from_path_arg(arg: &str) -> Option<String> {
+/// it should be treated as a path (with the name inferred by `naming`).
+/// Returns the inferred name and where it came from if so, or None if the
+/// argument looks like a bare project name.
+fn infer_name_from_path_arg(arg: &str, naming: NameStrategy) -> Option<(String, NameSource)> {
     if !looks_like_path(arg) && try_resolve_existing_dir(arg).is_none() {
         return None;
     }
-    let base = path_basename(&resolve_path(arg));
-    if base.is_empty() { None } else { Some(base) }
+    let (name, source) = project_name(&resolve_path(arg).ok()?, naming);
+    if name.is_empty() { None } else { Some((name, source)) }
+}
+
+/// The configured naming strategy, without first-run setup: commands that
+/// register a single directory work before vcode is configured.
+fn configured_naming() -> NameStrategy {
+    get_config().map(|c| c.naming).unwrap_or_default()
+}
+
+/// ` (name from <source>)`, or nothing for plain directory names.
+fn name_note(source: NameSource) -> String {
+    match source {
+        NameSource::Dirname => String::new(),
+        source => format!(" (name from {})", source),
+    }
 }
 
 fn looks_like_path(s: &str) -> bool {
@@ -138,13 +429,26 @@ fn looks_like_path(s: &str) -> bool {
         || s.contains('/')
 }
 
-fn handle_find_add(name: String) {
-    use inquire::Select;
+/// Unwraps a walk's results, warning first when the walk limits cut it short.
+fn walked_items<T>(walked: Walked<T>) -> Vec<T> {
+    if let Some(stopped) = walked.stopped {
+        log(
+            &format!(
+                "⚠ Walk {}; results are partial. Raise the limits with: vcode config set walk-max-entries|walk-timeout <n> (0 = unlimited)",
+                stopped
+            ),
+            LogType::Warning,
+        );
+    }
+    walked.items
+}
 
+fn handle_find_add(name: String, force: bool) {
     log(&format!("Searching for '{}'...", name), LogType::Info);
 
     match search_directory_by_name(&name) {
-        Ok(matches) => {
+        Ok(walked) => {
+            let matches = walked_items(walked);
             if matches.is_empty() {
                 log(&format!("✗ No directory named '{}' found", name), LogType::Error);
                 return;
@@ -157,9 +461,9 @@ fn handle_find_add(name: String) {
                     LogType::Success,
                 );
 
-                match set_project(&found.name, found.path.to_str().unwrap()) {
+                match path_str(&found.path).and_then(|path| set_project(&found.name, path, force)) {
                     Ok(()) => log(&format!("✓ Added project '{}'", found.name), LogType::Success),
-                    Err(_) => log("✗ Failed to add project", LogType::Error),
+                    Err(e) => log_write_error(&e),
                 }
                 return;
             }
@@ -171,30 +475,20 @@ fn handle_find_add(name: String) {
             );
             println!();
 
-            let options: Vec<String> = matches
+            let choices: Vec<Choice> = matches
                 .iter()
-                .map(|m| format!("{} → {}", m.name, m.path.display()))
+                .map(|m| Choice {
+                    name: m.name.clone(),
+                    path: m.path.display().to_string(),
+                })
                 .collect();
 
-            match Select::new("Select directory to add:", options)
-                .with_page_size(10)
-                .prompt()
-            {
-                Ok(selected) => {
-                    let idx = matches
-                        .iter()
-                        .position(|m| format!("{} → {}", m.name, m.path.display()) == selected)
-                        .unwrap();
-                    let chosen = &matches[idx];
-
-                    match set_project(&chosen.name, chosen.path.to_str().unwrap()) {
-                        Ok(()) => {
-                            log(&format!("✓ Added project '{}'", chosen.name), LogType::Success)
-                        }
-                        Err(_) => log("✗ Failed to add project", LogType::Error),
-                    }
-                }
-                Err(_) => {
+            match pick_choice("Select directory to add:", choices, 10) {
+                Some(chosen) => match set_project(&chosen.name, &chosen.path, force) {
+                    Ok(()) => log(&format!("✓ Added project '{}'", chosen.name), LogType::Success),
+                    Err(e) => log_write_error(&e),
+                },
+                None => {
                     log("Selection cancelled", LogType::Info);
                 }
             }
@@ -208,7 +502,7 @@ fn handle_find_add(name: String) {
 pub fn handle_remove(name: String) {
     match delete_project(&name) {
         Ok(()) => log(&format!("✓ Removed project '{}'", name), LogType::Success),
-        Err(_) => log(&format!("✗ Project '{}' not found", name), LogType::Error),
+        Err(e) => log(&format!("✗ {}", e), LogType::Error),
     }
 }
 
@@ -217,42 +511,66 @@ pub fn handle_list(
     interactive: bool,
     reuse: bool,
     editor_override: Option<String>,
-    sort: SortKey,
+    view: ListView,
     filter: Option<String>,
+    tags: Vec<String>,
 ) {
-    let projects = get_projects();
+    let mut projects = load_projects();
+
+    // Tag filtering is a cheap field check, so it runs before type detection
+    // and shrinks the set that has to hit the filesystem.
+    retain_tagged(&mut projects, &parse_tags(&tags));
+
+    // The interactive picker is a launcher, so it defaults to putting the
+    // projects you actually open at the top; the table stays alphabetical.
+    let sort = view.sort.unwrap_or(if interactive {
+        SortKey::Frecency
+    } else {
+        SortKey::Name
+    });
 
-    // Detect project types up front when needed (filter or sort=type) so the
-    // expensive marker-file scan runs once per project rather than once per
-    // pipeline stage.
-    let needs_types = filter.is_some() || sort == SortKey::Type;
+    // Detect project types up front when needed (filter, sort=type, or the
+    // table's Type column) so the marker-file and manifest scan runs once per
+    // project rather than once per pipeline stage.
+    let needs_types = filter.is_some() || sort == SortKey::Type || !(json || interactive);
     let mut rows: Vec<TypedRow> = projects
         .into_iter()
-        .map(|(name, path)| {
-            let ty = if needs_types {
-                detect_project_type(Path::new(&path))
+        .map(|(name, entry)| {
+            let detection = if needs_types {
+                detect_project(Path::new(&entry.path))
             } else {
-                None
+                Detection::default()
             };
-            TypedRow { name, path, ty }
+            TypedRow { name, entry, detection }
         })
         .collect();
 
     if let Some(type_filter) = filter.as_deref() {
-        let target = type_filter.to_lowercase();
-        rows.retain(|r| {
-            r.ty.map(|t| t.name().to_lowercase() == target)
-                .unwrap_or(false)
-        });
+        rows.retain(|r| r.detection.matches(type_filter));
     }
 
     sort_rows(&mut rows, sort);
 
+    if view.git {
+        let statuses = repo_statuses(&rows.iter().map(|r| r.entry.path.as_str()).collect::<Vec<_>>());
+        if json {
+            let map: HashMap<&str, GitEntry> = rows
+                .iter()
+                .zip(&statuses)
+                .map(|(r, git)| (r.name.as_str(), GitEntry { entry: &r.entry, git: git.as_ref() }))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&map).unwrap());
+            return;
+        }
+        let types: Vec<String> = rows.iter().map(|r| r.detection.label()).collect();
+        let pairs: Vec<(String, ProjectEntry)> = rows.into_iter().map(|r| (r.name, r.entry)).collect();
+        print_git_project_rows(&pairs, Some(&types), &statuses);
+        return;
+    }
+
     if json {
-        let map: HashMap<&str, &str> = rows
-            .iter()
-            .map(|r| (r.name.as_str(), r.path.as_str()))
-            .collect();
+        let map: HashMap<&str, &ProjectEntry> =
+            rows.iter().map(|r| (r.name.as_str(), &r.entry)).collect();
         println!("{}", serde_json::to_string_pretty(&map).unwrap());
         return;
     }
@@ -269,58 +587,124 @@ pub fn handle_list(
         return;
     }
 
-    let pairs: Vec<(String, String)> = rows.into_iter().map(|r| (r.name, r.path)).collect();
-    print_project_rows(&pairs);
+    let types: Vec<String> = rows.iter().map(|r| r.detection.label()).collect();
+    let pairs: Vec<(String, ProjectEntry)> = rows.into_iter().map(|r| (r.name, r.entry)).collect();
+    print_typed_project_rows(&pairs, &types);
+}
+
+/// A project with its git status, as `list --git --json` and `status --json`
+/// print it.
+#[derive(serde::Serialize)]
+struct GitEntry<'a> {
+    #[serde(flatten)]
+    entry: &'a ProjectEntry,
+    git: Option<&'a RepoStatus>,
+}
+
+/// `vcode status`: the registered repositories with uncommitted changes or
+/// commits to push or pull. Every repository is queried in parallel.
+pub fn handle_status(json: bool, tags: Vec<String>) {
+    let mut projects = load_projects();
+    retain_tagged(&mut projects, &parse_tags(&tags));
+    let mut rows: Vec<(String, ProjectEntry)> = projects.into_iter().collect();
+    rows.sort_by_key(|(name, _)| name.to_lowercase());
+
+    let statuses = repo_statuses(&rows.iter().map(|(_, e)| e.path.as_str()).collect::<Vec<_>>());
+    let repos = statuses.iter().flatten().count();
+    let (rows, statuses): (Vec<_>, Vec<_>) = rows
+        .into_iter()
+        .zip(statuses)
+        .filter(|(_, status)| status.as_ref().is_some_and(RepoStatus::needs_attention))
+        .unzip();
+
+    if json {
+        let map: std::collections::BTreeMap<&str, GitEntry> = rows
+            .iter()
+            .zip(&statuses)
+            .map(|((name, entry), git)| (name.as_str(), GitEntry { entry, git: git.as_ref() }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&map).unwrap());
+        return;
+    }
+    if repos == 0 {
+        log("No git repositories registered", LogType::Info);
+    } else if rows.is_empty() {
+        log(
+            &format!(
+                "✓ All {} repositor{} clean and in sync",
+                repos,
+                if repos == 1 { "y is" } else { "ies are" }
+            ),
+            LogType::Success,
+        );
+    } else {
+        print_git_project_rows(&rows, None, &statuses);
+    }
+}
+
+/// `vcode list --tags`: every tag in use with its project count.
+pub fn handle_list_tags(json: bool) {
+    let counts = tag_counts(&load_projects());
+    if json {
+        let map: std::collections::BTreeMap<&str, usize> =
+            counts.iter().map(|(t, n)| (t.as_str(), *n)).collect();
+        println!("{}", serde_json::to_string_pretty(&map).unwrap());
+    } else {
+        print_tag_counts(&counts);
+    }
 }
 
-/// A list row carrying its (optionally detected) project type so filter and
-/// sort can share one detection pass per project.
+/// A list row carrying its (optionally detected) project types so filter,
+/// sort and display can share one detection pass per project.
 struct TypedRow {
     name: String,
-    path: String,
-    ty: Option<ProjectType>,
+    entry: ProjectEntry,
+    detection: Detection,
 }
 
 fn sort_rows(rows: &mut [TypedRow], sort: SortKey) {
+    let now = now_secs();
     match sort {
-        SortKey::Name => rows.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase())),
-        SortKey::Path => rows.sort_by(|a, b| a.path.to_lowercase().cmp(&b.path.to_lowercase())),
+        SortKey::Name => rows.sort_by_key(|r| r.name.to_lowercase()),
+        SortKey::Path => rows.sort_by_key(|r| r.entry.path.to_lowercase()),
         SortKey::Type => rows.sort_by(|a, b| {
             // Unknowns sort last via the `~` sentinel.
-            let ta = a.ty.map(|t| t.name()).unwrap_or("~");
-            let tb = b.ty.map(|t| t.name()).unwrap_or("~");
-            ta.cmp(tb)
+            let label = |r: &TypedRow| {
+                if r.detection.is_empty() { "~".to_string() } else { r.detection.label() }
+            };
+            label(a)
+                .cmp(&label(b))
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        }),
+        SortKey::Frecency => rows.sort_by(|a, b| {
+            b.entry
+                .frecency(now)
+                .total_cmp(&a.entry.frecency(now))
                 .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
         }),
     }
 }
 
 fn run_interactive_open(rows: &[TypedRow], reuse: bool, editor_override: Option<String>) {
-    use inquire::Select;
-    let options: Vec<String> = rows
+    let choices: Vec<Choice> = rows
         .iter()
-        .map(|r| format!("{} → {}", r.name, r.path))
+        .map(|r| Choice {
+            name: r.name.clone(),
+            path: r.entry.path.clone(),
+        })
         .collect();
 
-    let selected = match Select::new("Select a project to open:", options.clone())
-        .with_page_size(15)
-        .prompt()
-    {
-        Ok(s) => s,
-        Err(_) => {
-            log("Selection cancelled", LogType::Info);
-            return;
-        }
+    let Some(chosen) = pick_choice("Select a project to open:", choices, 15) else {
+        log("Selection cancelled", LogType::Info);
+        return;
     };
 
-    let idx = options.iter().position(|o| o == &selected).unwrap();
-    let row = &rows[idx];
-    let config = get_config();
+    let config = load_config();
     let editor = editor_override.as_deref().unwrap_or(&config.default_editor);
-    open_and_exit(editor, &row.path, &row.name, reuse);
+    open_and_exit(editor, &chosen.path, &chosen.name, reuse, true, None);
 }
 
-pub fn handle_search(query: String, fs: bool, cd: bool) {
+pub fn handle_search(query: String, fs: bool, cd: bool, tags: Vec<String>, no_ignore: bool) {
     if fs {
         // `--fs` is an interactive add-from-filesystem workflow; `--cd` is a
         // copy-a-single-path action. They have no sensible combined meaning,
@@ -329,103 +713,85 @@ pub fn handle_search(query: String, fs: bool, cd: bool) {
             eprintln!("vcode: --cd cannot be combined with --fs");
             std::process::exit(1);
         }
-        handle_filesystem_search(query);
+        handle_filesystem_search(query, no_ignore);
         return;
     }
 
-    let projects = get_projects();
-    let query_lower = query.to_lowercase();
-    let filtered: HashMap<String, String> = projects
-        .iter()
-        .filter(|(name, path)| {
-            name.to_lowercase().contains(&query_lower) || path.to_lowercase().contains(&query_lower)
-        })
-        .map(|(k, v)| (k.clone(), v.clone()))
-        .collect();
+    // Same ranking as `vcode <name>`: exact, then prefix, then fuzzy name
+    // hits, then path-segment hits. `--cd` takes the top one.
+    let mut projects = load_projects();
+    retain_tagged(&mut projects, &parse_tags(&tags));
+    let ranked = rank_projects(&projects, &query);
 
     if cd {
-        if filtered.is_empty() {
-            eprintln!("vcode: no projects match '{}'", query);
-            std::process::exit(1);
+        match ranked.first() {
+            Some(best) => emit_cd_and_exit(&best.entry.path),
+            None => {
+                eprintln!("vcode: no projects match '{}'", query);
+                std::process::exit(1);
+            }
         }
-        // Best-match ranking: name-substring matches rank above path-only
-        // matches; among them, shorter names rank higher (closer-to-exact);
-        // alphabetical tiebreaker. Mirrors `fuzzy_match_projects`'s heuristic
-        // but extended to the name-OR-path filter `search` uses.
-        let mut ranked: Vec<(&String, &String)> = filtered.iter().collect();
-        ranked.sort_by_key(|(name, _)| {
-            let name_match = name.to_lowercase().contains(&query_lower);
-            (!name_match, name.len(), name.to_lowercase())
-        });
-        copy_cd_to_clipboard_or_exit(ranked[0].1);
     }
 
-    if filtered.is_empty() {
+    if ranked.is_empty() {
         log(
             &format!("No projects found matching '{}'", query),
             LogType::Info,
         );
     } else {
         log(&format!("Projects matching '{}':", query), LogType::Info);
-        print_table(&filtered);
+        let rows: Vec<(String, ProjectEntry)> =
+            ranked.into_iter().map(|m| (m.name, m.entry)).collect();
+        print_project_rows(&rows);
     }
 }
 
-/// Walk the configured projects root for directories whose name contains
+/// Walk the configured project roots for directories whose name contains
 /// `query`, then run the same multi-select + add flow that `vcode scan` uses.
-/// Lets the user adopt projects that live in their projects root but weren't
+/// Lets the user adopt projects that live in their project roots but weren't
 /// picked up by `vcode scan` (e.g. nested deeper than the scan depth).
-fn handle_filesystem_search(query: String) {
+fn handle_filesystem_search(query: String, no_ignore: bool) {
     if query.trim().is_empty() {
         log("✗ Query is empty", LogType::Error);
         std::process::exit(1);
     }
 
-    let config = get_config();
-    let base = resolve_path(&config.projects_root);
-    if !base.is_dir() {
+    let config = load_config();
+    let roots = existing_roots(&config);
+    if roots.is_empty() {
+        log("✗ None of the project roots is a directory", LogType::Error);
+        log("Add one with: vcode config root add <path>", LogType::Info);
+        std::process::exit(1);
+    }
+
+    let mut matches = Vec::new();
+    for (base, _) in &roots {
         log(
             &format!(
-                "✗ Projects root is not a directory: {}",
-                base.display()
+                "Searching '{}' for directories matching '{}'...",
+                base.display(),
+                query
             ),
-            LogType::Error,
-        );
-        log(
-            "Set it with: vcode config set projects-root <path>",
             LogType::Info,
         );
-        std::process::exit(1);
-    }
-
-    log(
-        &format!(
-            "Searching '{}' for directories matching '{}'...",
-            base.display(),
-            query
-        ),
-        LogType::Info,
-    );
-
-    let matches = match crate::scanner::search_directories(
-        &base,
-        &query,
-        crate::scanner::NameMatch::Substring,
-    ) {
-        Ok(m) => m,
-        Err(e) => {
-            log(&format!("✗ Search failed: {}", e), LogType::Error);
-            std::process::exit(1);
+        match crate::scanner::search_directories(
+            base,
+            &query,
+            crate::scanner::NameMatch::Substring,
+            !no_ignore,
+            scan_rules(),
+        ) {
+            Ok(walked) => matches.extend(walked_items(walked)),
+            Err(e) => {
+                log(&format!("✗ Search failed: {}", e), LogType::Error);
+                std::process::exit(1);
+            }
         }
-    };
+    }
 
     if matches.is_empty() {
         log(
-            &format!(
-                "No directories matching '{}' found under {}",
-                query,
-                base.display()
-            ),
+            &format!("No directories matching '{}' found in the project roots", query),
             LogType::Info,
         );
         return;
@@ -442,19 +808,22 @@ fn handle_filesystem_search(query: String) {
     // was used to add the entry. If canonicalization fails (e.g. stale
     // registry entry pointing nowhere), fall back to the raw path so we still
     // produce a usable set.
-    let already_registered: std::collections::HashSet<PathBuf> = get_projects()
+    let already_registered: std::collections::HashSet<PathBuf> = load_projects()
         .values()
-        .map(|p| std::fs::canonicalize(p).unwrap_or_else(|_| PathBuf::from(p)))
+        .map(|e| std::fs::canonicalize(&e.path).unwrap_or_else(|_| PathBuf::from(&e.path)))
         .collect();
 
     let found: Vec<crate::scanner::FoundProject> = matches
         .into_iter()
         .map(|m| {
             let canonical = std::fs::canonicalize(&m.path).unwrap_or_else(|_| m.path.clone());
+            let root = config.root_for(&canonical);
             crate::scanner::FoundProject {
-                project_type: detect_project_type(&canonical),
-                name: m.name,
+                detection: detect_project(&canonical),
+                name: root.map_or_else(|| m.name.clone(), |r| r.project_name(&m.name)),
+                tags: root.map(|r| r.tags.clone()).unwrap_or_default(),
                 path: canonical,
+                name_source: NameSource::Dirname,
             }
         })
         .collect();
@@ -498,7 +867,7 @@ fn handle_filesystem_search(query: String) {
         return;
     }
 
-    match add_projects(to_add) {
+    match add_projects(to_add, config.on_conflict, &[]) {
         Ok(n) => log(
             &format!(
                 "\n✓ Added {} project{}",
@@ -514,114 +883,511 @@ fn handle_filesystem_search(query: String) {
     }
 }
 
-pub fn handle_rename(old_name: String, new_name: String) {
-    match rename_project(&old_name, &new_name) {
+pub fn handle_tag(name: String, tags: Vec<String>) {
+    match tag_project(&name, &tags) {
+        Ok(added) if added.is_empty() => log(
+            &format!("'{}' already has those tags", name),
+            LogType::Info,
+        ),
+        Ok(added) => log(
+            &format!("✓ Tagged '{}' with {}", name, added.join(", ")),
+            LogType::Success,
+        ),
+        Err(e) => exit_with(e),
+    }
+}
+
+pub fn handle_untag(name: String, tags: Vec<String>) {
+    match untag_project(&name, &tags) {
+        Ok(removed) if removed.is_empty() => log(
+            &format!("'{}' has none of those tags", name),
+            LogType::Info,
+        ),
+        Ok(removed) => log(
+            &format!("✓ Removed {} from '{}'", removed.join(", "), name),
+            LogType::Success,
+        ),
+        Err(e) => exit_with(e),
+    }
+}
+
+pub fn handle_rename(old_name: String, new_name: String, force: bool) {
+    match rename_project(&old_name, &new_name, force) {
         Ok(()) => log(
             &format!("✓ Renamed '{}' → '{}'", old_name, new_name),
             LogType::Success,
         ),
-        Err(e) => log(&format!("✗ {}", e), LogType::Error),
+        Err(e) => log_write_error(&e),
     }
 }
 
-pub fn handle_scan(path: Option<String>, depth: u32, filter: String, no_review: bool) {
-    let config = get_config();
-    let base_path = match path {
-        Some(p) => resolve_path(&p),
-        None => PathBuf::from(&config.projects_root),
+pub fn handle_scan(
+    path: Option<String>,
+    depth: Option<u32>,
+    filter: String,
+    no_review: bool,
+    register: RegisterOptions,
+    walk: WalkOptions,
+) {
+    let tags = parse_tags(&register.tag);
+    let config = load_config();
+    let on_conflict = register.on_conflict.unwrap_or(config.on_conflict);
+    let naming = register.naming.unwrap_or(config.naming);
+    let Some(filter_mode) = parse_filter(&filter) else {
+        return;
     };
-
-    let filter_mode = match filter.to_lowercase().as_str() {
-        "all" => FilterMode::All,
-        "auto" => FilterMode::Auto,
-        _ => {
-            log("Invalid filter mode. Use 'auto' or 'all'", LogType::Error);
-            return;
-        }
+    let Some(scans) = scan_roots(&config, path, depth, filter_mode, walk, naming, false) else {
+        return;
     };
+    let found_projects: Vec<FoundProject> = scans
+        .into_iter()
+        .flat_map(|(_, walked)| walked_items(walked))
+        .collect();
+
+    if found_projects.is_empty() {
+        log("No projects found", LogType::Info);
+        return;
+    }
+
+    let project_count = found_projects.len();
 
     log(
         &format!(
-            "Scanning {} at depth {} (filter: {})...",
-            base_path.display(),
-            depth,
-            filter
+            "✓ Found {} project{}",
+            project_count,
+            if project_count == 1 { "" } else { "s" }
         ),
-        LogType::Info,
+        LogType::Success,
     );
 
-    match scan_projects(&base_path, depth, filter_mode) {
-        Ok(found_projects) => {
-            if found_projects.is_empty() {
-                log("No projects found", LogType::Info);
-                return;
-            }
+    review_and_add(found_projects, no_review, on_conflict, &tags);
+}
+
+fn parse_filter(filter: &str) -> Option<FilterMode> {
+    match filter.to_lowercase().as_str() {
+        "all" => Some(FilterMode::All),
+        "auto" => Some(FilterMode::Auto),
+        _ => {
+            log("Invalid filter mode. Use 'auto' or 'all'", LogType::Error);
+            None
+        }
+    }
+}
 
-            let project_count = found_projects.len();
+/// Scans `path`, or every configured root when there's none, naming what's
+/// found with `naming` and the prefix and tags of the root it's under.
+/// `None` after logging why when there was nothing to scan or a scan
+/// failed. `quiet` leaves out the progress lines, for output that must
+/// stay machine-readable.
+fn scan_roots(
+    config: &Config,
+    path: Option<String>,
+    depth: Option<u32>,
+    filter_mode: FilterMode,
+    walk: WalkOptions,
+    naming: NameStrategy,
+    quiet: bool,
+) -> Option<Vec<(PathBuf, Walked<FoundProject>)>> {
+    // An explicit path picks up the settings of the root it lives in, if
+    // any; no path scans every configured root.
+    let targets: Vec<(PathBuf, Option<&ProjectRoot>)> = match path {
+        Some(p) => {
+            let base_path = resolve_arg(&p);
+            let root = config.root_for(&base_path);
+            vec![(base_path, root)]
+        }
+        None => existing_roots(config)
+            .into_iter()
+            .map(|(base_path, root)| (base_path, Some(root)))
+            .collect(),
+    };
+    if targets.is_empty() {
+        log("✗ None of the project roots is a directory", LogType::Error);
+        log("Add one with: vcode config root add <path>", LogType::Info);
+        return None;
+    }
 
+    let mut scans = Vec::new();
+    for (base_path, root) in targets {
+        let depth = depth.unwrap_or_else(|| root.map_or(1, |r| r.depth));
+        if !quiet {
             log(
                 &format!(
-                    "✓ Found {} project{}",
-                    project_count,
-                    if project_count == 1 { "" } else { "s" }
+                    "Scanning {} at depth {} (filter: {})...",
+                    base_path.display(),
+                    depth,
+                    if filter_mode == FilterMode::All { "all" } else { "auto" }
                 ),
-                LogType::Success,
+                LogType::Info,
             );
+        }
 
-            let projects_to_add = if no_review {
-                found_projects
-            } else {
-                match interactive_select_projects(found_projects) {
-                    Ok(selected) => selected,
-                    Err(_) => {
-                        log("Scan cancelled", LogType::Info);
-                        return;
+        match scan_projects(&base_path, depth, filter_mode, !walk.no_members, !walk.no_ignore, scan_rules()) {
+            Ok(mut walked) => {
+                for project in &mut walked.items {
+                    // Members are named after their workspace already.
+                    if project.name_source != NameSource::Member {
+                        (project.name, project.name_source) = project_name(&project.path, naming);
+                    }
+                    if let Some(root) = root {
+                        project.name = root.project_name(&project.name);
+                        project.tags = root.tags.clone();
                     }
                 }
-            };
-
-            if projects_to_add.is_empty() {
-                log("No projects selected", LogType::Info);
-                return;
+                scans.push((base_path, walked));
             }
-
-            match add_projects(projects_to_add) {
-                Ok(added_count) => {
-                    log(
-                        &format!(
-                            "\n✓ Added {} project{}",
-                            added_count,
-                            if added_count == 1 { "" } else { "s" }
-                        ),
-                        LogType::Success,
-                    );
-                }
-                Err(e) => {
-                    log(&format!("✗ Failed to add projects: {}", e), LogType::Error);
-                }
+            Err(e) => {
+                log(&format!("✗ Failed to scan: {}", e), LogType::Error);
+                return None;
             }
         }
-        Err(e) => {
-            log(&format!("✗ Failed to scan: {}", e), LogType::Error);
-        }
     }
+    Some(scans)
 }
 
-pub fn handle_config(action: Option<ConfigAction>) {
-    match action {
-        None => config_show(),
-        Some(ConfigAction::Show) => config_show(),
-        Some(ConfigAction::Set { key, value }) => config_set(&key, &value),
-        Some(ConfigAction::Editors) => config_editors(),
-        Some(ConfigAction::Add) => config_add_editor(),
-        Some(ConfigAction::Remove { name }) => config_remove_editor(&name),
-        Some(ConfigAction::Edit) => config_edit(),
-        Some(ConfigAction::Reset) => config_reset(),
+/// How `vcode scan --sync` gets from the plan to the registry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyncMode {
+    /// Pick the changes to apply
+    Review,
+    /// Apply every change
+    ApplyAll,
+    /// Print the plan as JSON and change nothing
+    DryRun,
+}
+
+/// `vcode scan --sync`: reconciles the registry with each scanned root,
+/// adding new projects, following moved ones and dropping vanished ones.
+pub fn handle_scan_sync(
+    path: Option<String>,
+    depth: Option<u32>,
+    filter: String,
+    mode: SyncMode,
+    register: RegisterOptions,
+    walk: WalkOptions,
+) {
+    let tags = parse_tags(&register.tag);
+    let config = load_config();
+    let on_conflict = register.on_conflict.unwrap_or(config.on_conflict);
+    let naming = register.naming.unwrap_or(config.naming);
+    let Some(filter_mode) = parse_filter(&filter) else {
+        std::process::exit(1);
+    };
+    let dry_run = mode == SyncMode::DryRun;
+    let Some(scans) = scan_roots(&config, path, depth, filter_mode, walk, naming, dry_run) else {
+        std::process::exit(1);
+    };
+    let scans = scans
+        .into_iter()
+        .map(|(root, walked)| {
+            let partial = walked.stopped.is_some();
+            let found = if dry_run { walked.items } else { walked_items(walked) };
+            (root, found, partial)
+        })
+        .collect();
+    let plans = plan_sync(scans, &load_projects());
+
+    if dry_run {
+        let json: Vec<_> = plans.iter().map(SyncPlan::to_json).collect();
+        println!("{}", serde_json::to_string_pretty(&json).unwrap());
+        return;
+    }
+    if plans.iter().all(SyncPlan::is_empty) {
+        // Still worth a write: it records remotes entries are missing.
+        if let Err(e) = apply_sync(&plans, on_conflict, &tags) {
+            log_write_error(&e);
+        }
+        log("✓ The registry is in sync with the project roots", LogType::Success);
+        return;
+    }
+
+    let plans = if mode == SyncMode::Review {
+        match select_sync_changes(plans) {
+            Ok(plans) => plans,
+            Err(_) => {
+                log("Sync cancelled", LogType::Info);
+                return;
+            }
+        }
+    } else {
+        plans
+    };
+
+    match apply_sync(&plans, on_conflict, &tags) {
+        Ok(applied) => log(
+            &format!(
+                "\n✓ Added {}, moved {}, removed {}",
+                applied.added, applied.moved, applied.removed
+            ),
+            LogType::Success,
+        ),
+        Err(e) => log_write_error(&e),
+    }
+}
+
+/// The review-then-register tail shared by `scan` and `scan --pending`:
+/// lets the user pick from `found` (unless `no_review`) and registers the
+/// picks. Returns false when the user cancelled the review.
+fn review_and_add(
+    found: Vec<FoundProject>,
+    no_review: bool,
+    on_conflict: ConflictStrategy,
+    tags: &[String],
+) -> bool {
+    let projects_to_add = if no_review {
+        found
+    } else {
+        match interactive_select_projects(found) {
+            Ok(selected) => selected,
+            Err(_) => {
+                log("Scan cancelled", LogType::Info);
+                return false;
+            }
+        }
+    };
+
+    if projects_to_add.is_empty() {
+        log("No projects selected", LogType::Info);
+        return true;
+    }
+
+    match add_projects(projects_to_add, on_conflict, tags) {
+        Ok(added_count) => {
+            log(
+                &format!(
+                    "\n✓ Added {} project{}",
+                    added_count,
+                    if added_count == 1 { "" } else { "s" }
+                ),
+                LogType::Success,
+            );
+        }
+        Err(e) => {
+            log(&format!("✗ Failed to add projects: {}", e), LogType::Error);
+        }
+    }
+    true
+}
+
+/// `vcode scan --pending`: review the projects `vcode watch --review` queued.
+pub fn handle_scan_pending(no_review: bool, on_conflict: Option<ConflictStrategy>, tags: Vec<String>) {
+    let tags = parse_tags(&tags);
+    let on_conflict = on_conflict.unwrap_or(load_config().on_conflict);
+    let queue = get_pending().unwrap_or_else(|e| exit_with(e));
+    if queue.is_empty() {
+        log(
+            "No projects waiting for review. `vcode watch --review` queues new ones here",
+            LogType::Info,
+        );
+        return;
+    }
+
+    // Queued directories may have been registered or removed since.
+    let registered: std::collections::HashSet<String> =
+        load_projects().into_values().map(|e| e.path).collect();
+    let found: Vec<FoundProject> = queue
+        .iter()
+        .filter(|p| p.path.is_dir() && !p.path.to_str().is_some_and(|s| registered.contains(s)))
+        .map(|p| FoundProject {
+            name: p.name.clone(),
+            path: p.path.clone(),
+            detection: detect_project(&p.path),
+            tags: p.tags.clone(),
+            name_source: p.name_source,
+        })
+        .collect();
+    let reviewed: Vec<PathBuf> = queue.into_iter().map(|p| p.path).collect();
+
+    if found.is_empty() {
+        log("Queued projects are all registered or gone already", LogType::Info);
+    } else {
+        log(
+            &format!(
+                "✓ {} project{} waiting for review",
+                found.len(),
+                if found.len() == 1 { "" } else { "s" }
+            ),
+            LogType::Success,
+        );
+        if !review_and_add(found, no_review, on_conflict, &tags) {
+            // Cancelling keeps the queue for next time.
+            return;
+        }
+    }
+    if let Err(e) = remove_pending(&reviewed) {
+        log_write_error(&e);
+    }
+}
+
+/// `vcode watch`: registers (or with `review`, queues) projects as they
+/// appear under the project roots, and follows registered ones that move
+/// or disappear. Runs until interrupted. `depth` overrides each root's own.
+pub fn handle_watch(depth: Option<u32>, review: bool, tags: Vec<String>) {
+    #[cfg(target_os = "linux")]
+    watch_project_roots(depth, review, parse_tags(&tags));
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (depth, review, tags);
+        log("✗ vcode watch uses inotify and is only available on Linux", LogType::Error);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn watch_project_roots(depth: Option<u32>, review: bool, tags: Vec<String>) {
+    use crate::scanner::{WatchEvent, Watcher};
+
+    let config = load_config();
+    let roots: Vec<(PathBuf, u32)> = existing_roots(&config)
+        .into_iter()
+        .map(|(path, root)| (path, depth.unwrap_or(root.depth)))
+        .collect();
+    if roots.is_empty() {
+        log("✗ None of the project roots is a directory", LogType::Error);
+        std::process::exit(1);
+    }
+    let mut watcher = Watcher::new(&roots, scan_rules()).unwrap_or_else(|e| exit_with(e));
+    let listed: Vec<String> = roots
+        .iter()
+        .map(|(path, depth)| format!("{} (depth {})", path.display(), depth))
+        .collect();
+    log(
+        &format!(
+            "Watching {} ({} directories). Press Ctrl-C to stop.",
+            listed.join(", "),
+            watcher.watch_count()
+        ),
+        LogType::Info,
+    );
+
+    loop {
+        let events = watcher
+            .poll(std::time::Duration::from_secs(1))
+            .unwrap_or_else(|e| exit_with(e));
+        for event in events {
+            match event {
+                WatchEvent::Project(found) => watched_project(&config, found, review, &tags),
+                WatchEvent::Moved { from, to } => watched_relocation(&from, Some(&to)),
+                WatchEvent::Removed(path) => watched_relocation(&path, None),
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn watched_project(config: &Config, mut found: FoundProject, review: bool, tags: &[String]) {
+    let path = path_str(&found.path).unwrap_or_default().to_string();
+    let projects = load_projects();
+    if projects.values().any(|e| e.path == path && e.missing_since.is_none()) {
+        return;
+    }
+    // A flagged project whose directory came back: re-cloned in place, or
+    // moved back in from outside the tree. Elsewhere, only its git remote or
+    // package name identifies it; two unrelated `api/` clones share a
+    // directory name, so that's left to `vcode repair` to ask about.
+    let flagged = || projects.iter().filter(|(_, e)| e.missing_since.is_some());
+    let returning = flagged().find(|(_, e)| e.path == path).or_else(|| {
+        let identity = Identity::of(&found.path);
+        [MoveMatch::GitRemote, MoveMatch::Manifest]
+            .into_iter()
+            .find_map(|by| flagged().find(|(name, e)| by.matches(name, e, &found.path, &identity)))
+    });
+    if let Some((name, _)) = returning {
+        match relocate_project(name, &path) {
+            Ok(_) => log(&format!("✓ '{}' is back at {}", name, path), LogType::Success),
+            Err(e) => log_write_error(&e),
+        }
+        return;
+    }
+
+    (found.name, found.name_source) = project_name(&found.path, config.naming);
+    if let Some(root) = config.root_for(&found.path) {
+        found.name = root.project_name(&found.name);
+        found.tags = root.tags.clone();
+    }
+    if review {
+        match queue_pending(&found.name, found.name_source, &found.path, &found.tags) {
+            Ok(true) => log(
+                &format!("• Queued {} for review: vcode scan --pending", found.display_name()),
+                LogType::Info,
+            ),
+            Ok(false) => {}
+            Err(e) => log_write_error(&e),
+        }
+        return;
+    }
+    if let Err(e) = add_projects(vec![found], config.on_conflict, tags) {
+        log_write_error(&e);
+    }
+}
+
+/// Points registered projects at or below `from` to their new place under
+/// `to`, or flags them missing when `to` is `None`.
+#[cfg(target_os = "linux")]
+fn watched_relocation(from: &Path, to: Option<&Path>) {
+    let affected = |entry: &ProjectEntry| Path::new(&entry.path).starts_with(from);
+    // Most moves are of directories nobody registered; don't rewrite the
+    // registry for those.
+    if !load_projects().values().any(affected) {
+        return;
+    }
+
+    let now = now_secs();
+    let result = update_projects(|projects| {
+        let mut changed = Vec::new();
+        for (name, entry) in projects.iter_mut().filter(|(_, e)| affected(e)) {
+            match to {
+                Some(to) => {
+                    let rest = Path::new(&entry.path).strip_prefix(from).unwrap_or(Path::new(""));
+                    let moved = if rest.as_os_str().is_empty() { to.to_path_buf() } else { to.join(rest) };
+                    entry.move_to(path_str(&moved)?.to_string());
+                }
+                None => {
+                    entry.missing_since.get_or_insert(now);
+                }
+            }
+            changed.push((name.clone(), entry.path.clone()));
+        }
+        Ok(changed)
+    });
+
+    match result {
+        Ok(changed) => {
+            for (name, path) in changed {
+                match to {
+                    Some(_) => log(&format!("→ '{}' moved to {}", name, path), LogType::Success),
+                    None => log(
+                        &format!(
+                            "⚠ '{}' is gone from {}; flagged as missing (vcode update or vcode prune)",
+                            name, path
+                        ),
+                        LogType::Warning,
+                    ),
+                }
+            }
+        }
+        Err(e) => log_write_error(&e),
+    }
+}
+
+pub fn handle_config(action: Option<ConfigAction>) {
+    match action {
+        None => config_show(),
+        Some(ConfigAction::Show) => config_show(),
+        Some(ConfigAction::Set { key, value }) => config_set(&key, &value),
+        Some(ConfigAction::Editors) => config_editors(),
+        Some(ConfigAction::Add) => config_add_editor(),
+        Some(ConfigAction::Remove { name }) => config_remove_editor(&name),
+        Some(ConfigAction::Marker { action }) => config_marker(action),
+        Some(ConfigAction::Skip { action }) => config_skip(action),
+        Some(ConfigAction::Root { action }) => config_root(action),
+        Some(ConfigAction::Edit) => config_edit(),
+        Some(ConfigAction::Reset) => config_reset(),
     }
 }
 
 fn config_show() {
-    let config = get_config();
+    let config = load_config();
 
     println!();
     println!("  {}", "┌──────────────────────────────────────────────────┐".dimmed());
@@ -631,12 +1397,54 @@ fn config_show() {
         format!("{:<10}", "Editor").cyan().bold(),
         config.default_editor.white()
     );
+    for (i, root) in config.roots.iter().enumerate() {
+        println!(
+            "  {}  {}  {}",
+            "│".dimmed(),
+            format!("{:<10}", if i == 0 { "Roots" } else { "" }).cyan().bold(),
+            describe_root(root).white()
+        );
+    }
+    println!(
+        "  {}  {}  {}",
+        "│".dimmed(),
+        format!("{:<10}", "Conflicts").cyan().bold(),
+        format!("{:?}", config.on_conflict).to_lowercase().white()
+    );
     println!(
         "  {}  {}  {}",
         "│".dimmed(),
-        format!("{:<10}", "Projects").cyan().bold(),
-        config.projects_root.white()
+        format!("{:<10}", "Naming").cyan().bold(),
+        config.naming.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default().white()
     );
+    if !config.markers.is_empty() {
+        let markers: Vec<String> = config
+            .markers
+            .iter()
+            .map(|m| format!("{} → {}", m.pattern, m.project_type))
+            .collect();
+        println!(
+            "  {}  {}  {}",
+            "│".dimmed(),
+            format!("{:<10}", "Markers").cyan().bold(),
+            markers.join(", ").white()
+        );
+    }
+    if !config.skip_dirs.is_empty() {
+        let changes: Vec<String> = config
+            .skip_dirs
+            .add
+            .iter()
+            .map(|d| format!("+{}", d))
+            .chain(config.skip_dirs.remove.iter().map(|d| format!("-{}", d)))
+            .collect();
+        println!(
+            "  {}  {}  {}",
+            "│".dimmed(),
+            format!("{:<10}", "Skip dirs").cyan().bold(),
+            changes.join(" ").white()
+        );
+    }
     println!("  {}", "└──────────────────────────────────────────────────┘".dimmed());
     println!();
     println!(
@@ -647,36 +1455,70 @@ fn config_show() {
 }
 
 fn config_set(key: &str, value: &str) {
-    let mut config = get_config();
-
-    match key {
-        "editor" => {
-            if !config.editors.contains_key(value) {
-                log(&format!("✗ Unknown editor '{}'. Use 'vcode config editors' to see available options.", value), LogType::Error);
-                return;
+    // Paths resolve against this process's working directory, before the
+    // config is locked.
+    let root = (key == "projects-root").then(|| resolve_arg(value));
+
+    let result = edit_config(|config| {
+        match key {
+            "editor" => {
+                if !config.editors.contains_key(value) {
+                    return Err(format!("Unknown editor '{}'. Use 'vcode config editors' to see available options.", value));
+                }
+                config.default_editor = value.to_string();
             }
-            config.default_editor = value.to_string();
-        }
-        "projects-root" => {
-            let path = resolve_path(value);
-            if !path.exists() {
-                log(&format!("✗ Path does not exist: {}", path.display()), LogType::Error);
-                return;
+            "projects-root" => {
+                let path = root.unwrap_or_default();
+                if !path.exists() {
+                    return Err(format!("Path does not exist: {}", path.display()));
+                }
+                set_primary_root(config, path.to_string_lossy().to_string());
+            }
+            "terminal" => {
+                let argv = split_command_line(value);
+                if argv.is_empty() {
+                    return Err("Terminal command is empty (e.g. kitty -e)".to_string());
+                }
+                config.terminal = Some(argv);
+            }
+            "walk-max-entries" | "walk-timeout" => {
+                let Ok(n) = value.parse::<u64>() else {
+                    return Err(format!("Expected a whole number (0 = unlimited), got '{}'", value));
+                };
+                if key == "walk-timeout" {
+                    config.walk_limits.timeout_secs = n;
+                } else {
+                    config.walk_limits.max_entries = n as usize;
+                }
+            }
+            "on-conflict" => match ConflictStrategy::from_str(value, true) {
+                Ok(strategy) => config.on_conflict = strategy,
+                Err(_) => return Err(format!("Unknown strategy '{}'. Use skip, overwrite, suffix or parent", value)),
+            },
+            "naming" => match NameStrategy::from_str(value, true) {
+                Ok(strategy) => config.naming = strategy,
+                Err(_) => {
+                    return Err(format!(
+                        "Unknown strategy '{}'. Use dirname, manifest, git-remote or parent/dir",
+                        value
+                    ));
+                }
+            },
+            _ => {
+                return Err(format!("Unknown key '{}'. Valid keys: editor, projects-root, on-conflict, naming, terminal, walk-max-entries, walk-timeout", key));
             }
-            config.projects_root = path.to_string_lossy().to_string();
-        }
-        _ => {
-            log(&format!("✗ Unknown key '{}'. Valid keys: editor, projects-root", key), LogType::Error);
-            return;
         }
-    }
+        Ok(())
+    });
 
-    update_config(&config).expect("Failed to update config");
-    log(&format!("✓ Set {} = {}", key, value), LogType::Success);
+    match result {
+        Ok(()) => log(&format!("✓ Set {} = {}", key, value), LogType::Success),
+        Err(message) => log(&format!("✗ {}", message), LogType::Error),
+    }
 }
 
 fn config_editors() {
-    let config = get_config();
+    let config = load_config();
 
     println!();
     println!("  ┌─────────────────────────────────────────────────────────┐");
@@ -692,18 +1534,22 @@ fn config_editors() {
     for (name, editor_config) in editors {
         let is_default = name == &config.default_editor;
         let marker = if is_default { " ←" } else { "" };
+        let mode = match editor_config.mode_or_default() {
+            EditorMode::GuiDetached => String::new(),
+            m => format!("  ({})", mode_label(m)),
+        };
 
-        let args_display = if editor_config.args.is_empty() {
-            String::new()
-        } else {
-            format!("  [{}]", editor_config.args.join(" "))
+        let command_display = match &editor_config.template {
+            Some(template) => template.join(" "),
+            None if editor_config.args.is_empty() => editor_config.command.clone(),
+            None => format!("{}  [{}]", editor_config.command, editor_config.args.join(" ")),
         };
 
         println!(
             "  {:<width$}  {}{}{}",
             name,
-            editor_config.command,
-            args_display,
+            command_display,
+            mode,
             marker,
             width = max_name_len
         );
@@ -720,7 +1566,7 @@ fn config_editors() {
 fn config_add_editor() {
     use inquire::{Confirm, Text};
 
-    let mut config = get_config();
+    let config = load_config();
 
     println!();
     println!("  Add Custom Editor");
@@ -776,32 +1622,270 @@ fn config_add_editor() {
         .ok()
         .filter(|s| !s.trim().is_empty());
 
+    // Optional full command line. Empty keeps the classic
+    // `command args [reuse] path` behaviour built from the answers above.
+    let template = Text::new("  Command template (optional):")
+        .with_help_message(
+            "e.g. kitty -e nvim {path}  ·  placeholders: {path} {name} {file} {line} {column} {reuse} {type}",
+        )
+        .with_default("")
+        .prompt()
+        .ok()
+        .map(|t| split_command_line(&t))
+        .filter(|argv| !argv.is_empty());
+
+    let modes = [
+        EditorMode::GuiDetached,
+        EditorMode::TerminalForeground,
+        EditorMode::TerminalNewWindow,
+    ];
+    let inferred = default_mode(&command, &args);
+    let mode = inquire::Select::new("  Mode:", modes.iter().map(|m| mode_label(*m)).collect())
+        .with_starting_cursor(modes.iter().position(|m| *m == inferred).unwrap_or(0))
+        .with_help_message("Terminal editors (nvim, helix) run in the foreground")
+        .prompt()
+        .ok()
+        .and_then(|label| modes.into_iter().find(|m| mode_label(*m) == label))
+        .unwrap_or(inferred);
+
     let editor_config = EditorConfig {
         command,
         args,
         reuse_flag,
+        file_args: None,
+        template,
+        // Only persist a mode that differs from what would be inferred, so
+        // the config stays as small as the user's answers.
+        mode: (mode != inferred).then_some(mode),
     };
 
-    config.add_editor(name.clone(), editor_config);
-    update_config(&config).expect("Failed to update config");
+    edit_config(|config| config.add_editor(name.clone(), editor_config));
 
     println!();
     log(&format!("✓ Added editor '{}'", name), LogType::Success);
 }
 
+fn mode_label(mode: EditorMode) -> &'static str {
+    match mode {
+        EditorMode::GuiDetached => "gui-detached",
+        EditorMode::TerminalForeground => "terminal-foreground",
+        EditorMode::TerminalNewWindow => "terminal-new-window",
+    }
+}
+
 fn config_remove_editor(name: &str) {
-    let mut config = get_config();
+    let result = edit_config(|config| {
+        if name == config.default_editor {
+            return Err("Cannot remove the default editor. Change it first with: vcode config set editor <other>".to_string());
+        }
+        if config.remove_editor(name) {
+            Ok(())
+        } else {
+            Err(format!("Editor '{}' not found", name))
+        }
+    });
 
-    if name == config.default_editor {
-        log("✗ Cannot remove the default editor. Change it first with: vcode config set editor <other>", LogType::Error);
-        return;
+    match result {
+        Ok(()) => log(&format!("✓ Removed editor '{}'", name), LogType::Success),
+        Err(message) => log(&format!("✗ {}", message), LogType::Error),
     }
+}
 
-    if config.remove_editor(name) {
-        update_config(&config).expect("Failed to update config");
-        log(&format!("✓ Removed editor '{}'", name), LogType::Success);
-    } else {
-        log(&format!("✗ Editor '{}' not found", name), LogType::Error);
+fn config_marker(action: MarkerAction) {
+    let config = load_config();
+
+    match action {
+        MarkerAction::List => {
+            if config.markers.is_empty() {
+                log("No marker rules. Add one with: vcode config marker add <pattern> <type>", LogType::Info);
+                return;
+            }
+            let width = config.markers.iter().map(|m| m.pattern.len()).max().unwrap_or(0);
+            println!();
+            for rule in &config.markers {
+                println!("  {}  →  {}", format!("{:<width$}", rule.pattern).cyan(), rule.project_type.white());
+            }
+            println!();
+        }
+        MarkerAction::Add { pattern, type_name } => {
+            let pattern = pattern.trim().trim_end_matches('/').to_string();
+            let type_name = type_name.trim().to_string();
+            if pattern.is_empty() || Path::new(&pattern).is_absolute() || pattern.split('/').any(|s| s == "..") {
+                log(&format!("✗ Invalid marker '{}': use a name or path relative to the project", pattern), LogType::Error);
+                return;
+            }
+            if type_name.is_empty() {
+                log("✗ Type name can't be empty", LogType::Error);
+                return;
+            }
+            edit_config(|config| {
+                config.markers.retain(|m| m.pattern != pattern);
+                config.markers.push(MarkerRule {
+                    pattern: pattern.clone(),
+                    project_type: type_name.clone(),
+                });
+            });
+            log(&format!("✓ Directories with {} are now detected as {}", pattern, type_name), LogType::Success);
+        }
+        MarkerAction::Remove { pattern } => {
+            let removed = edit_config(|config| {
+                let before = config.markers.len();
+                config.markers.retain(|m| m.pattern != pattern);
+                config.markers.len() != before
+            });
+            if !removed {
+                log(&format!("✗ No marker rule for '{}'", pattern), LogType::Error);
+                return;
+            }
+            log(&format!("✓ Removed marker rule '{}'", pattern), LogType::Success);
+        }
+    }
+}
+
+fn config_skip(action: SkipAction) {
+    let config = load_config();
+    let skip = &config.skip_dirs;
+
+    match action {
+        SkipAction::List => {
+            if skip.is_empty() {
+                log("Using the built-in skip list. Change it with: vcode config skip add|remove <dir>", LogType::Info);
+                return;
+            }
+            println!();
+            for name in &skip.add {
+                println!("  {} {}", "+".green(), name);
+            }
+            for name in &skip.remove {
+                println!("  {} {}", "-".red(), name);
+            }
+            println!();
+        }
+        SkipAction::Add { name } | SkipAction::Remove { name } if name.is_empty() || name.contains('/') => {
+            log(&format!("✗ Invalid directory name '{}'", name), LogType::Error);
+        }
+        // Each command first undoes its opposite, so `remove go` followed
+        // by `add go` returns to the built-in behaviour.
+        SkipAction::Add { name } => {
+            edit_config(|config| {
+                let skip = &mut config.skip_dirs;
+                skip.remove.retain(|d| d != &name);
+                if !scan_rules_for(skip).skips(&name) {
+                    skip.add.push(name.clone());
+                }
+            });
+            log(&format!("✓ Scans will skip '{}' directories", name), LogType::Success);
+        }
+        SkipAction::Remove { name } => {
+            edit_config(|config| {
+                let skip = &mut config.skip_dirs;
+                skip.add.retain(|d| d != &name);
+                if scan_rules_for(skip).skips(&name) {
+                    skip.remove.push(name.clone());
+                }
+            });
+            log(&format!("✓ Scans will enter '{}' directories", name), LogType::Success);
+        }
+    }
+}
+
+fn config_root(action: RootAction) {
+    let config = load_config();
+
+    match action {
+        RootAction::List => {
+            if config.roots.is_empty() {
+                log("No project roots. Add one with: vcode config root add <path>", LogType::Info);
+                return;
+            }
+            println!();
+            for (i, root) in config.roots.iter().enumerate() {
+                println!("  {}  {}", format!("{}.", i + 1).dimmed(), describe_root(root).white());
+            }
+            println!();
+        }
+        RootAction::Add { path, depth, tag, prefix } => {
+            let resolved = resolve_arg(&path);
+            if !resolved.is_dir() {
+                log(&format!("✗ Not a directory: {}", resolved.display()), LogType::Error);
+                return;
+            }
+            let tags = match normalize_tags(&tag) {
+                Ok(tags) => tags,
+                Err(e) => {
+                    log(&format!("✗ {}", e), LogType::Error);
+                    return;
+                }
+            };
+            let path = resolved.to_string_lossy().to_string();
+            let root = ProjectRoot {
+                path: path.clone(),
+                depth,
+                tags,
+                prefix: prefix.filter(|p| !p.is_empty()),
+            };
+            // Re-adding a root updates it in place and keeps its position.
+            let updated = edit_config(|config| {
+                match config.roots.iter_mut().find(|r| resolve_arg(&r.path) == resolved) {
+                    Some(existing) => {
+                        *existing = root;
+                        true
+                    }
+                    None => {
+                        config.roots.push(root);
+                        false
+                    }
+                }
+            });
+            let verb = if updated { "Updated" } else { "Added" };
+            log(&format!("✓ {} project root {}", verb, path), LogType::Success);
+        }
+        RootAction::Remove { path } => {
+            let resolved = resolve_arg(&path);
+            let result = edit_config(|config| {
+                let Some(index) = config.roots.iter().position(|r| resolve_arg(&r.path) == resolved) else {
+                    return Err(format!("{} is not a project root", resolved.display()));
+                };
+                if config.roots.len() == 1 {
+                    return Err("Can't remove the only project root; add another first".to_string());
+                }
+                Ok(config.roots.remove(index))
+            });
+            match result {
+                Ok(removed) => log(&format!("✓ Removed project root {}", removed.path), LogType::Success),
+                Err(message) => log(&format!("✗ {}", message), LogType::Error),
+            }
+        }
+    }
+}
+
+/// One line about a root for `config show` and `config root list`.
+fn describe_root(root: &ProjectRoot) -> String {
+    let mut line = format!("{} (depth {}", root.path, root.depth);
+    if !root.tags.is_empty() {
+        line.push_str(&format!(", tags {}", root.tags.join(",")));
+    }
+    if let Some(prefix) = &root.prefix {
+        line.push_str(&format!(", prefix '{}'", prefix));
+    }
+    line.push(')');
+    line
+}
+
+/// Points the first root at `path`, for the single-root `projects-root`
+/// setting.
+fn set_primary_root(config: &mut Config, path: String) {
+    match config.roots.first_mut() {
+        Some(root) => root.path = path,
+        None => config.roots.push(ProjectRoot::new(path)),
+    }
+}
+
+/// Rules for just a skip list, to ask what it would do.
+fn scan_rules_for(skip: &SkipDirs) -> ScanRules {
+    ScanRules {
+        skip_dirs: skip.clone(),
+        ..Default::default()
     }
 }
 
@@ -814,7 +1898,7 @@ fn config_edit() {
     println!("  └─────────────────────────────────────────────────────────┘");
     println!();
 
-    let mut config = get_config();
+    let mut config = load_config();
 
     let options = vec![
         "Set default editor",
@@ -829,40 +1913,50 @@ fn config_edit() {
             Ok("Set default editor") => {
                 let editor_names: Vec<&str> = config.editors.keys().map(|s| s.as_str()).collect();
                 if let Ok(selected) = Select::new("  Select editor:", editor_names).prompt() {
-                    config.default_editor = selected.to_string();
-                    update_config(&config).expect("Failed to update config");
+                    let selected = selected.to_string();
+                    config = edit_config(|c| {
+                        c.default_editor = selected.clone();
+                        c.clone()
+                    });
                     println!();
                     log(&format!("✓ Default editor: {}", selected), LogType::Success);
                 }
             }
             Ok("Set projects root") => {
                 use inquire::Text;
+                let current = config.primary_root().map(|r| r.path.clone()).unwrap_or_default();
                 if let Ok(path) = Text::new("  Projects directory:")
-                    .with_default(&config.projects_root)
+                    .with_default(&current)
                     .prompt()
                 {
-                    config.projects_root = resolve_path(&path).to_string_lossy().to_string();
-                    update_config(&config).expect("Failed to update config");
+                    let root = resolve_arg(&path).to_string_lossy().to_string();
+                    config = edit_config(|c| {
+                        set_primary_root(c, root);
+                        c.clone()
+                    });
                     println!();
                     log("✓ Projects root updated", LogType::Success);
                 }
             }
             Ok("Add custom editor") => {
                 config_add_editor();
-                config = get_config();
+                config = load_config();
             }
             Ok("Remove editor") => {
                 let editor_names: Vec<String> = config.editors.keys().cloned().collect();
                 let editor_refs: Vec<&str> = editor_names.iter().map(|s| s.as_str()).collect();
                 if let Ok(selected) = Select::new("  Select editor to remove:", editor_refs).prompt() {
-                    if selected == config.default_editor {
-                        println!();
-                        log("✗ Cannot remove the default editor", LogType::Error);
-                    } else {
-                        config.remove_editor(selected);
-                        update_config(&config).expect("Failed to update config");
-                        println!();
+                    let selected = selected.to_string();
+                    let removed = edit_config(|c| {
+                        let removed = selected != c.default_editor && c.remove_editor(&selected);
+                        config = c.clone();
+                        removed
+                    });
+                    println!();
+                    if removed {
                         log(&format!("✓ Removed '{}'", selected), LogType::Success);
+                    } else {
+                        log("✗ Cannot remove the default editor", LogType::Error);
                     }
                 }
             }
@@ -889,7 +1983,10 @@ fn config_reset() {
 
     match confirm {
         Ok(true) => {
-            reset_config();
+            if let Err(e) = reset_config() {
+                exit_with(e);
+            }
+            init_config();
             println!();
             log("✓ Configuration reset", LogType::Success);
         }
@@ -919,19 +2016,20 @@ pub fn handle_clear(yes: bool) {
 
     match reset_projects() {
         Ok(()) => log("✓ All projects cleared", LogType::Success),
-        Err(_) => log("✗ Failed to clear projects", LogType::Error),
+        Err(e) => log(&format!("✗ Failed to clear projects: {}", e), LogType::Error),
     }
 }
 
 pub fn handle_open_project(
     project_name: String,
+    file: Option<String>,
     reuse: bool,
     editor_override: Option<String>,
     cd: bool,
 ) {
-    let projects = get_projects();
+    let projects = load_projects();
 
-    // Resolve the editor lazily. First-run `get_config()` prompts on stdin
+    // Resolve the editor lazily. First-run `load_config()` prompts on stdin
     // for projects-root / default-editor, and `--cd` doesn't need either of
     // them — so we only pay that cost (and only fail on it) when we're
     // actually about to open something.
@@ -939,17 +2037,17 @@ pub fn handle_open_project(
         if let Some(e) = &editor_override {
             e.clone()
         } else {
-            get_config().default_editor
+            load_config().default_editor
         }
     };
 
     // 1. Exact match in the registry
-    if let Some(path) = projects.get(&project_name) {
+    if let Some(entry) = projects.get(&project_name) {
         if cd {
-            copy_cd_to_clipboard_or_exit(path);
+            emit_cd_and_exit(&entry.path);
         }
         let editor = resolve_editor();
-        open_and_exit(&editor, path, &project_name, reuse);
+        open_and_exit(&editor, &entry.path, &project_name, reuse, true, file.as_deref());
     }
 
     // 2. Path fallback — if the argument resolves to an existing directory,
@@ -957,98 +2055,143 @@ pub fn handle_open_project(
     if let Some(resolved) = try_resolve_existing_dir(&project_name) {
         let resolved_str = resolved.to_string_lossy();
         if cd {
-            copy_cd_to_clipboard_or_exit(&resolved_str);
+            emit_cd_and_exit(&resolved_str);
         }
         let display = path_basename(&resolved);
         let editor = resolve_editor();
-        open_and_exit(&editor, &resolved_str, &display, reuse);
+        open_and_exit(&editor, &resolved_str, &display, reuse, false, file.as_deref());
     }
 
-    // 3. Fuzzy match against project names (case-insensitive substring)
-    let matches = fuzzy_match_projects(&projects, &project_name);
-    match matches.len() {
-        0 => {
-            log(
-                &format!("✗ Project '{}' not found", project_name),
-                LogType::Error,
-            );
-            log(
-                "\nTip: Use 'vcode list' to see all projects or 'vcode add' to add a new one",
-                LogType::Info,
-            );
-            std::process::exit(1);
-        }
-        1 => {
-            let (name, path) = &matches[0];
-            if cd {
-                copy_cd_to_clipboard_or_exit(path);
-            }
-            log(
-                &format!("→ Matched '{}'", name),
-                LogType::Info,
-            );
-            let editor = resolve_editor();
-            open_and_exit(&editor, path, name, reuse);
-        }
-        _ => {
-            use inquire::Select;
-            let options: Vec<String> = matches
-                .iter()
-                .map(|(n, p)| format!("{} → {}", n, p))
-                .collect();
-            match Select::new(
-                &format!("Multiple matches for '{}'. Select one:", project_name),
-                options,
-            )
-            .with_page_size(10)
-            .prompt()
-            {
-                Ok(selected) => {
-                    let name = selected.split(" → ").next().unwrap();
-                    let path = matches
-                        .iter()
-                        .find(|(n, _)| n == name)
-                        .map(|(_, p)| p)
-                        .unwrap();
-                    if cd {
-                        copy_cd_to_clipboard_or_exit(path);
+    // 3. `project:sub/dir` — a directory inside a registered project
+    if let Some((project, sub)) = split_subtarget(&project_name) {
+        let (name, dirs) = resolve_subtarget(&projects, project, sub).unwrap_or_else(|e| exit_with(e));
+        let dir = match unique_subdir(&dirs, sub) {
+            Some(dir) => dir.clone(),
+            None => {
+                let root = PathBuf::from(&projects[&name].path);
+                let choices: Vec<Choice> = dirs
+                    .iter()
+                    .map(|d| Choice {
+                        name: subtarget_label(&name, &root, d),
+                        path: d.to_string_lossy().into_owned(),
+                    })
+                    .collect();
+                let prompt = format!("Multiple directories match '{}'. Select one:", sub);
+                match pick_choice(&prompt, choices, 10) {
+                    Some(chosen) => PathBuf::from(chosen.path),
+                    None => {
+                        log("Selection cancelled", LogType::Info);
+                        std::process::exit(1);
                     }
-                    let editor = resolve_editor();
-                    open_and_exit(&editor, path, name, reuse);
-                }
-                Err(_) => {
-                    log("Selection cancelled", LogType::Info);
-                    std::process::exit(1);
                 }
             }
+        };
+        let dir = dir.to_string_lossy();
+        if cd {
+            emit_cd_and_exit(&dir);
         }
+        // Credited to the parent project: that's what the user is working in.
+        open_and_exit(&resolve_editor(), &dir, &name, reuse, true, file.as_deref());
+    }
+
+    // 4. Fuzzy match against project names and path segments
+    let matches = rank_projects(&projects, &project_name);
+    if matches.is_empty() {
+        log(
+            &format!("✗ Project '{}' not found", project_name),
+            LogType::Error,
+        );
+        log(
+            "\nTip: Use 'vcode list' to see all projects or 'vcode add' to add a new one",
+            LogType::Info,
+        );
+        std::process::exit(1);
+    }
+
+    if let Some(m) = unambiguous(&matches) {
+        if cd {
+            emit_cd_and_exit(&m.entry.path);
+        }
+        log(&format!("→ Matched '{}'", m.name), LogType::Info);
+        let editor = resolve_editor();
+        open_and_exit(&editor, &m.entry.path, &m.name, reuse, true, file.as_deref());
     }
-}
 
-/// Returns `(name, path)` pairs whose name contains `query` (case-insensitive),
-/// sorted shortest-name-first so that closer-to-exact matches surface above
-/// looser ones (e.g. `api` ranks above `api-service-backend`).
-fn fuzzy_match_projects(
-    projects: &std::collections::HashMap<String, String>,
-    query: &str,
-) -> Vec<(String, String)> {
-    let q = query.to_lowercase();
-    let mut out: Vec<(String, String)> = projects
+    let choices: Vec<Choice> = matches
         .iter()
-        .filter(|(name, _)| name.to_lowercase().contains(&q))
-        .map(|(k, v)| (k.clone(), v.clone()))
+        .map(|m| Choice {
+            name: m.name.clone(),
+            path: m.entry.path.clone(),
+        })
         .collect();
-    out.sort_by_key(|(n, _)| (n.len(), n.to_lowercase()));
-    out
+    let prompt = format!("Multiple matches for '{}'. Select one:", project_name);
+    match pick_choice(&prompt, choices, 10) {
+        Some(chosen) => {
+            if cd {
+                emit_cd_and_exit(&chosen.path);
+            }
+            let editor = resolve_editor();
+            open_and_exit(&editor, &chosen.path, &chosen.name, reuse, true, file.as_deref());
+        }
+        None => {
+            log("Selection cancelled", LogType::Info);
+            std::process::exit(1);
+        }
+    }
 }
 
-fn open_and_exit(editor: &str, path: &str, label: &str, reuse: bool) -> ! {
-    match open_with_editor(editor, path, reuse) {
+/// Launches `path` in `editor` and exits. When `registered` is set, `label`
+/// is the project's registry name and the open is recorded in its usage
+/// stats; path-fallback opens pass `false` since there is nothing to credit.
+/// `file` (`src/main.rs:42`) is resolved against `path` and focused.
+fn open_and_exit(
+    editor: &str,
+    path: &str,
+    label: &str,
+    reuse: bool,
+    registered: bool,
+    file: Option<&str>,
+) -> ! {
+    let config = load_config();
+    let target = file.map(|f| FileTarget::parse(Path::new(path), f));
+    if let Some(t) = &target
+        && !t.path.exists()
+    {
+        log(
+            &format!("⚠ {} does not exist yet", t.path.display()),
+            LogType::Warning,
+        );
+    }
+    let request = OpenRequest {
+        path,
+        name: label,
+        reuse,
+        file: target.as_ref(),
+    };
+
+    // Usage tracking is best-effort: a registry write failure must not turn
+    // a successful open into an error exit.
+    let record = || {
+        if registered && let Err(e) = record_open(label) {
+            log(&format!("⚠ Could not record usage: {}", e), LogType::Warning);
+        }
+        log(
+            &format!("Opening '{}' in {}", label, editor),
+            LogType::Success,
+        );
+    };
+
+    // A foreground editor replaces this process, so there's no "after" to
+    // record the open in; do it up front.
+    let foreground = effective_mode(&config, editor) == EditorMode::TerminalForeground;
+    if foreground {
+        record();
+    }
+    match open_with_editor(&config, editor, &request) {
         Ok(()) => {
-            log(
-                &format!("Opening '{}' in {}", label, editor),
-                LogType::Success,
-            );
+            if !foreground {
+                record();
+            }
             std::process::exit(0);
         }
         Err(e) => {
@@ -1058,7 +2201,13 @@ fn open_and_exit(editor: &str, path: &str, label: &str, reuse: bool) -> ! {
     }
 }
 
-pub fn handle_here(name: Option<String>, reuse: bool, editor_override: Option<String>) {
+pub fn handle_here(
+    name: Option<String>,
+    force: bool,
+    naming: Option<NameStrategy>,
+    reuse: bool,
+    editor_override: Option<String>,
+) {
     let cwd = match std::env::current_dir() {
         Ok(p) => p,
         Err(e) => {
@@ -1067,89 +2216,314 @@ pub fn handle_here(name: Option<String>, reuse: bool, editor_override: Option<St
         }
     };
 
-    let project_name = name.filter(|n| !n.trim().is_empty()).unwrap_or_else(|| path_basename(&cwd));
+    let (project_name, source) = match name.filter(|n| !n.trim().is_empty()) {
+        Some(name) => (name, NameSource::Dirname),
+        None => project_name(&cwd, naming.unwrap_or_else(configured_naming)),
+    };
     if project_name.is_empty() {
         log("✗ Could not infer project name from current directory", LogType::Error);
         std::process::exit(1);
     }
 
     let path_str = cwd.to_string_lossy().into_owned();
-    if let Err(e) = set_project(&project_name, &path_str) {
-        log(&format!("✗ Failed to register project: {}", e), LogType::Error);
+    if let Err(e) = set_project(&project_name, &path_str, force) {
+        log_write_error(&e);
         std::process::exit(1);
     }
     log(
-        &format!("✓ Registered '{}' → {}", project_name, path_str),
+        &format!("✓ Registered '{}' → {}{}", project_name, path_str, name_note(source)),
         LogType::Success,
     );
 
-    let config = get_config();
+    let config = load_config();
     let editor = editor_override.as_deref().unwrap_or(&config.default_editor);
-    open_and_exit(editor, &path_str, &project_name, reuse);
+    open_and_exit(editor, &path_str, &project_name, reuse, true, None);
 }
 
-pub fn handle_where(name: String, cd: bool) {
-    let projects = get_projects();
+pub fn handle_where(name: String, cd: bool, tags: Vec<String>) {
+    // Errors go to stderr here, like every other `where` diagnostic, so
+    // `cd "$(vcode where x)"` never captures them as a path.
+    let fail = |e: Error| -> ! {
+        eprintln!("vcode: {}", e);
+        std::process::exit(1);
+    };
+    let mut projects = get_projects().unwrap_or_else(|e| fail(e));
+    let tags = normalize_tags(&tags).unwrap_or_else(|e| fail(e));
+    retain_tagged(&mut projects, &tags);
 
     // When --cd is set we delegate to the shared clipboard exit helper;
     // otherwise we keep the existing `println!` shell-substitution behaviour.
     let emit = |path: &str| {
         if cd {
-            copy_cd_to_clipboard_or_exit(path);
+            emit_cd_and_exit(path);
         }
         println!("{}", path);
     };
 
     // Exact match → emit path.
-    if let Some(path) = projects.get(&name) {
-        emit(path);
+    if let Some(entry) = projects.get(&name) {
+        emit(&entry.path);
         return;
     }
 
     // Path fallback — emit the canonical resolved path so scripts can use it
-    // with `cd $(vcode where ../foo)`.
-    if let Some(resolved) = try_resolve_existing_dir(&name) {
+    // with `cd $(vcode where ../foo)`. A bare directory has no tags, so a
+    // tag filter rules it out.
+    if tags.is_empty()
+        && let Some(resolved) = try_resolve_existing_dir(&name)
+    {
         emit(&resolved.display().to_string());
         return;
     }
 
-    // Fuzzy match. For scripting safety, only emit when there's a single hit;
-    // multiple matches go to stderr so command substitution captures nothing.
-    let matches = fuzzy_match_projects(&projects, &name);
-    match matches.len() {
-        0 => {
-            eprintln!("vcode: project '{}' not found", name);
+    // `project:sub/dir`. Same scripting rule as below: a single answer or
+    // nothing on stdout.
+    if let Some((project, sub)) = split_subtarget(&name) {
+        let (project, dirs) = resolve_subtarget(&projects, project, sub).unwrap_or_else(|e| {
+            eprintln!("vcode: {}", e);
             std::process::exit(1);
+        });
+        match unique_subdir(&dirs, sub) {
+            Some(dir) => emit(&dir.to_string_lossy()),
+            None => {
+                let root = PathBuf::from(&projects[&project].path);
+                eprintln!("vcode: ambiguous directory '{}', candidates:", sub);
+                for dir in dirs.iter().take(10) {
+                    eprintln!("  {} → {}", subtarget_label(&project, &root, dir), dir.display());
+                }
+                std::process::exit(1);
+            }
         }
-        1 => {
-            emit(&matches[0].1);
-        }
-        _ => {
+        return;
+    }
+
+    // Fuzzy match. For scripting safety, only emit when the best tier has a
+    // single hit; ambiguous matches go to stderr so command substitution
+    // captures nothing.
+    let matches = rank_projects(&projects, &name);
+    if matches.is_empty() {
+        eprintln!("vcode: project '{}' not found", name);
+        std::process::exit(1);
+    }
+    match unambiguous(&matches) {
+        Some(m) => emit(&m.entry.path),
+        None => {
             eprintln!("vcode: ambiguous match for '{}', candidates:", name);
-            for (n, p) in &matches {
-                eprintln!("  {} → {}", n, p);
+            for m in ambiguous_candidates(&matches) {
+                eprintln!("  {} → {}", m.name, m.entry.path);
             }
             std::process::exit(1);
         }
     }
 }
 
+/// `vcode init <shell>`: prints the shell integration script to `eval`.
+pub fn handle_init(shell: InitShell, cmd: String) {
+    match init_script(shell, &cmd) {
+        Some(script) => print!("{}", script),
+        None => {
+            eprintln!(
+                "vcode: '{}' can't be used as the jump command (use letters, digits, '-' or '_')",
+                cmd
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Hidden `vcode __complete`, printing one candidate per line.
+///
+/// - `__complete projects|editors|tags|types [prefix]` lists one kind
+///   directly (the `vcode init` jump command uses `projects`).
+/// - `__complete line -- <words>` takes the command line up to the cursor
+///   and works out the kind itself; it exits 1 when the word isn't
+///   something vcode can complete, so the shell falls back to clap's
+///   static completions.
+///
+/// Errors print nothing, since anything on stdout would be offered as a
+/// candidate.
+pub fn handle_complete(cli: &clap::Command, kind: String, args: Vec<String>) {
+    let (candidates, prefix) = match kind.as_str() {
+        "line" => match completion_context(cli, &args) {
+            Some(c) => (c, args.last().cloned().unwrap_or_default()),
+            None => std::process::exit(1),
+        },
+        "projects" => (Candidates::Projects, args.concat()),
+        "editors" => (Candidates::Editors, args.concat()),
+        "tags" => (Candidates::Tags, args.concat()),
+        "types" => (Candidates::Types, args.concat()),
+        _ => std::process::exit(1),
+    };
+
+    let prefix = prefix.to_lowercase();
+    for value in completion_values(cli, candidates) {
+        if value.to_lowercase().starts_with(&prefix) {
+            println!("{}", value);
+        }
+    }
+}
+
+/// Every value for `candidates`, best first: projects by frecency, the rest
+/// alphabetically.
+fn completion_values(cli: &clap::Command, candidates: Candidates) -> Vec<String> {
+    let projects = || get_projects().unwrap_or_default();
+    match candidates {
+        Candidates::Projects => projects_by_frecency(&projects()),
+        Candidates::ProjectsOrSubcommands => {
+            let mut values = projects_by_frecency(&projects());
+            values.extend(
+                cli.get_subcommands()
+                    .filter(|c| !c.is_hide_set())
+                    .map(|c| c.get_name().to_string()),
+            );
+            values
+        }
+        Candidates::Editors => {
+            let mut editors: Vec<String> = get_config()
+                .map(|c| c.editors.into_keys().collect())
+                .unwrap_or_default();
+            editors.sort();
+            editors
+        }
+        Candidates::Tags => tag_counts(&projects()).into_iter().map(|(t, _)| t).collect(),
+        Candidates::Types => ProjectType::ALL
+            .iter()
+            .map(|t| t.name())
+            .chain(Framework::ALL.iter().map(|f| f.name()))
+            .map(str::to_lowercase)
+            .collect(),
+        Candidates::Fixed(values) => values,
+    }
+}
+
+fn projects_by_frecency(projects: &HashMap<String, ProjectEntry>) -> Vec<String> {
+    let now = now_secs();
+    let mut names: Vec<(&String, f64)> = projects
+        .iter()
+        .map(|(name, entry)| (name, entry.frecency(now)))
+        .collect();
+    names.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    names.into_iter().map(|(name, _)| name.clone()).collect()
+}
+
+/// Lists the `limit` most recently opened projects, or with `interactive`
+/// offers them in a picker and reopens the chosen one.
+pub fn handle_recent(limit: usize, interactive: bool, reuse: bool, editor_override: Option<String>) {
+    let mut opened: Vec<(String, ProjectEntry)> = load_projects()
+        .into_iter()
+        .filter(|(_, e)| e.last_opened.is_some())
+        .collect();
+    opened.sort_by(|(na, a), (nb, b)| {
+        b.last_opened
+            .cmp(&a.last_opened)
+            .then_with(|| na.to_lowercase().cmp(&nb.to_lowercase()))
+    });
+    opened.truncate(limit);
+
+    if !interactive || opened.is_empty() {
+        print_recent_rows(&opened, now_secs());
+        return;
+    }
+
+    let rows: Vec<TypedRow> = opened
+        .into_iter()
+        .map(|(name, entry)| TypedRow {
+            name,
+            entry,
+            detection: Detection::default(),
+        })
+        .collect();
+    run_interactive_open(&rows, reuse, editor_override);
+}
+
+/// Splits `project:sub/dir`. Only called once the whole argument has failed
+/// to match a project name, so names that contain `:` still open normally.
+fn split_subtarget(arg: &str) -> Option<(&str, &str)> {
+    arg.split_once(':').filter(|(project, _)| !project.is_empty())
+}
+
+/// Resolves the project half of `project:sub` (exactly, or by an
+/// unambiguous fuzzy match) and the directories `sub` may mean inside it,
+/// best first. Errors when either half finds nothing.
+fn resolve_subtarget(
+    projects: &HashMap<String, ProjectEntry>,
+    project: &str,
+    sub: &str,
+) -> Result<(String, Vec<PathBuf>), String> {
+    let name = if projects.contains_key(project) {
+        project.to_string()
+    } else {
+        let matches = rank_projects(projects, project);
+        match unambiguous(&matches) {
+            Some(m) => m.name.clone(),
+            None if matches.is_empty() => return Err(Error::NotFound(project.to_string()).to_string()),
+            None => {
+                let names: Vec<&str> = ambiguous_candidates(&matches).map(|m| m.name.as_str()).collect();
+                return Err(format!("Project '{}' is ambiguous: {}", project, names.join(", ")));
+            }
+        }
+    };
+
+    let dirs = resolve_subdir(Path::new(&projects[&name].path), sub, scan_rules()).map_err(|e| e.to_string())?;
+    if dirs.is_empty() {
+        return Err(format!("No directory matching '{}' in project '{}'", sub, name));
+    }
+    Ok((name, dirs))
+}
+
+/// The directory `sub` unambiguously names: the only candidate, or the only
+/// one whose final component is exactly `sub`'s last segment.
+fn unique_subdir<'a>(dirs: &'a [PathBuf], sub: &str) -> Option<&'a PathBuf> {
+    if let [only] = dirs {
+        return Some(only);
+    }
+    let last = sub.trim_end_matches('/').rsplit('/').next()?.to_lowercase();
+    let mut exact = dirs
+        .iter()
+        .filter(|d| d.file_name().is_some_and(|n| n.to_string_lossy().to_lowercase() == last));
+    match (exact.next(), exact.next()) {
+        (Some(dir), None) => Some(dir),
+        _ => None,
+    }
+}
+
+/// `project:relative/dir`, for pickers and ambiguity messages.
+fn subtarget_label(project: &str, root: &Path, dir: &Path) -> String {
+    let root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let rel = dir.strip_prefix(&root).unwrap_or(dir);
+    format!("{}:{}", project, rel.display())
+}
+
+/// The matches worth listing when a lookup is ambiguous: the best tier only,
+/// since a lower tier would never have been picked anyway.
+fn ambiguous_candidates(matches: &[ProjectMatch]) -> impl Iterator<Item = &ProjectMatch> {
+    let best = matches.first().map(|m| m.tier);
+    matches.iter().take_while(move |m| Some(m.tier) == best)
+}
+
 pub fn handle_prune(yes: bool) {
-    let mut projects = get_projects();
+    let projects = load_projects();
     let stale: Vec<(String, String)> = projects
         .iter()
-        .filter(|(_, path)| {
-            let p = Path::new(path);
+        .filter(|(_, entry)| {
+            let p = Path::new(&entry.path);
             !p.exists() || !p.is_dir()
         })
-        .map(|(k, v)| (k.clone(), v.clone()))
+        .map(|(k, v)| (k.clone(), v.path.clone()))
         .collect();
 
     if stale.is_empty() {
         log("✓ No stale projects to prune", LogType::Success);
         return;
     }
+    if !yes {
+        log("If some were moved, `vcode repair` can find them instead", LogType::Info);
+    }
+    prune_entries(stale, yes);
+}
 
+/// Lists `stale` (name, path) pairs, asks (unless `yes`) and removes them.
+fn prune_entries(stale: Vec<(String, String)>, yes: bool) {
     log(
         &format!(
             "Found {} stale project{}:",
@@ -1173,15 +2547,26 @@ pub fn handle_prune(yes: bool) {
         }
     }
 
-    for (n, _) in &stale {
-        projects.remove(n);
-    }
-    match write_projects(&projects) {
-        Ok(()) => log(
+    // The confirmation prompt can sit open indefinitely, so the registry is
+    // re-read under the lock here rather than held across it. Only entries
+    // that are still present and still point at the same dead path are
+    // removed, so a concurrent `vcode update` fixing one isn't undone.
+    let result = update_projects(|projects| {
+        let mut removed = 0;
+        for (n, p) in &stale {
+            if projects.get(n).is_some_and(|e| &e.path == p && !Path::new(p).is_dir()) {
+                projects.remove(n);
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    });
+    match result {
+        Ok(removed) => log(
             &format!(
                 "✓ Pruned {} project{}",
-                stale.len(),
-                if stale.len() == 1 { "" } else { "s" }
+                removed,
+                if removed == 1 { "" } else { "s" }
             ),
             LogType::Success,
         ),
@@ -1192,13 +2577,170 @@ pub fn handle_prune(yes: bool) {
     }
 }
 
-pub fn handle_update(name: String, path: String) {
-    if !get_projects().contains_key(&name) {
-        log(&format!("✗ Project '{}' not found", name), LogType::Error);
-        std::process::exit(1);
+/// What to do with one stale project in `vcode repair`.
+enum RepairChoice {
+    Move(PathBuf),
+    Remove,
+    Keep,
+}
+
+/// `vcode repair`: looks under the project roots for where each stale
+/// project went and points it there. Projects it can't find are offered
+/// for removal, as `vcode prune` would.
+pub fn handle_repair(yes: bool) {
+    let projects = load_projects();
+    let mut stale: Vec<(&String, &ProjectEntry)> = projects
+        .iter()
+        .filter(|(_, e)| !Path::new(&e.path).is_dir())
+        .collect();
+    if stale.is_empty() {
+        log("✓ No stale projects to repair", LogType::Success);
+        return;
+    }
+    stale.sort_by(|a, b| a.0.cmp(b.0));
+
+    log(
+        &format!(
+            "Looking for {} stale project{} under the project roots...",
+            stale.len(),
+            if stale.len() == 1 { "" } else { "s" }
+        ),
+        LogType::Info,
+    );
+    let config = load_config();
+    let registered: std::collections::HashSet<&str> = projects.values().map(|e| e.path.as_str()).collect();
+    let mut candidates: Vec<(PathBuf, Identity)> = Vec::new();
+    for (root, _) in existing_roots(&config) {
+        match find_projects(&root, SEARCH_DEPTH, true, scan_rules()) {
+            Ok(walked) => candidates.extend(
+                walked_items(walked)
+                    .into_iter()
+                    .filter(|p| !p.path.to_str().is_some_and(|s| registered.contains(s)))
+                    .map(|p| {
+                        let identity = Identity::of(&p.path);
+                        (p.path, identity)
+                    }),
+            ),
+            Err(e) => log(
+                &format!("⚠ Skipping {}: {}", root.display(), e),
+                LogType::Warning,
+            ),
+        }
+    }
+
+    let mut taken = std::collections::HashSet::new();
+    let mut moves: Vec<(String, String, PathBuf)> = Vec::new();
+    let mut removals: Vec<(String, String)> = Vec::new();
+    let mut unfound: Vec<(String, String)> = Vec::new();
+    for (name, entry) in stale {
+        let mut options: Vec<(&PathBuf, MoveMatch)> = candidates
+            .iter()
+            .filter(|(path, _)| !taken.contains(path))
+            .filter_map(|(path, identity)| best_match(name, entry, path, identity).map(|m| (path, m)))
+            .collect();
+        options.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+        if options.is_empty() {
+            unfound.push((name.clone(), entry.path.clone()));
+            continue;
+        }
+
+        let choice = if yes {
+            // Unattended, only a match that beats every other one is taken.
+            if options.len() == 1 || options[0].1 < options[1].1 {
+                RepairChoice::Move(options[0].0.clone())
+            } else {
+                log(
+                    &format!("⚠ '{}' could be in several places; run without --yes to pick", name),
+                    LogType::Warning,
+                );
+                RepairChoice::Keep
+            }
+        } else {
+            match pick_repair(name, entry, &options) {
+                Some(choice) => choice,
+                None => {
+                    log("Repair cancelled", LogType::Info);
+                    return;
+                }
+            }
+        };
+        match choice {
+            RepairChoice::Move(to) => {
+                taken.insert(to.clone());
+                moves.push((name.clone(), entry.path.clone(), to));
+            }
+            RepairChoice::Remove => removals.push((name.clone(), entry.path.clone())),
+            RepairChoice::Keep => {}
+        }
+    }
+
+    if !moves.is_empty() || !removals.is_empty() {
+        apply_repairs(&moves, &removals);
+    }
+    if !unfound.is_empty() {
+        log("No new location found for these:", LogType::Info);
+        prune_entries(unfound, yes);
+    }
+}
+
+/// Asks where the stale project `name` went. `None` when the user cancels.
+fn pick_repair(name: &str, entry: &ProjectEntry, options: &[(&PathBuf, MoveMatch)]) -> Option<RepairChoice> {
+    use inquire::Select;
+
+    let mut labels: Vec<String> = options
+        .iter()
+        .map(|(path, by)| format!("Move to {} ({})", path.display(), by.reason()))
+        .collect();
+    labels.push("Remove from the registry".to_string());
+    labels.push("Leave as is".to_string());
+
+    println!();
+    let picked = Select::new(&format!("'{}' is gone from {}.", name, entry.path), labels)
+        .raw_prompt()
+        .ok()?;
+    Some(match picked.index {
+        i if i < options.len() => RepairChoice::Move(options[i].0.clone()),
+        i if i == options.len() => RepairChoice::Remove,
+        _ => RepairChoice::Keep,
+    })
+}
+
+/// Applies the moves and removals `vcode repair` settled on, to entries
+/// that still point at the directory it saw gone.
+fn apply_repairs(moves: &[(String, String, PathBuf)], removals: &[(String, String)]) {
+    let still_gone = |entry: Option<&ProjectEntry>, path: &str| {
+        entry.is_some_and(|e| e.path == path && !Path::new(path).is_dir())
+    };
+    let result = update_projects(|projects| {
+        let mut changed = Vec::new();
+        for (name, from, to) in moves {
+            if still_gone(projects.get(name), from)
+                && let Some(entry) = projects.get_mut(name)
+            {
+                entry.move_to(path_str(to)?.to_string());
+                changed.push(format!("→ '{}' moved to {}", name, to.display()));
+            }
+        }
+        for (name, path) in removals {
+            if still_gone(projects.get(name), path) {
+                projects.remove(name);
+                changed.push(format!("- '{}' removed", name));
+            }
+        }
+        Ok(changed)
+    });
+    match result {
+        Ok(changed) => {
+            for line in changed {
+                log(&line, LogType::Success);
+            }
+        }
+        Err(e) => log_write_error(&e),
     }
+}
 
-    match set_project_validated(&name, &path) {
+pub fn handle_update(name: String, path: String) {
+    match relocate_project(&name, &path) {
         Ok(resolved) => log(
             &format!("✓ Updated '{}' → {}", name, resolved.display()),
             LogType::Success,
@@ -1209,3 +2751,92 @@ pub fn handle_update(name: String, path: String) {
         }
     }
 }
+
+/// Index subcommand actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum IndexAction {
+    /// Walk the project roots and home directory and rebuild the index from scratch
+    Rebuild,
+    /// Show what the index covers and how fresh it is
+    Status,
+}
+
+pub fn handle_index(action: IndexAction) {
+    match action {
+        IndexAction::Rebuild => index_rebuild(),
+        IndexAction::Status => index_status(),
+    }
+}
+
+/// The trees the index covers: the project roots and the home directory.
+fn index_roots(config: &Config) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = config.roots.iter().map(|r| resolve_arg(&r.path)).collect();
+    roots.extend(dirs::home_dir());
+    roots
+}
+
+fn index_rebuild() {
+    let roots = index_roots(&load_config());
+    let listed: Vec<String> = roots.iter().map(|r| r.display().to_string()).collect();
+    log(&format!("Indexing {}...", listed.join(", ")), LogType::Info);
+
+    let started = std::time::Instant::now();
+    match rebuild_index(&roots, scan_rules()) {
+        Ok(index) => log(
+            &format!(
+                "✓ Indexed {} directories ({} projects) in {:.1}s",
+                index.dir_count(),
+                index.project_count(),
+                started.elapsed().as_secs_f64()
+            ),
+            LogType::Success,
+        ),
+        Err(e) => exit_with(e),
+    }
+}
+
+fn index_status() {
+    let index = match load_index() {
+        Ok(Some(index)) => index,
+        Ok(None) => {
+            log("No index yet. Build one with: vcode index rebuild", LogType::Info);
+            return;
+        }
+        Err(e) => exit_with(e),
+    };
+
+    let now = now_secs();
+    let row = |label: &str, value: String| {
+        println!("  {}  {}", format!("{:<12}", label).cyan().bold(), value.white());
+    };
+    println!();
+    if let Ok(path) = get_index_path() {
+        row("File", path.display().to_string());
+    }
+    for root in index.roots() {
+        row("Root", root.display().to_string());
+    }
+    row("Directories", index.dir_count().to_string());
+    row("Projects", index.project_count().to_string());
+    row("Built", relative_age(now.saturating_sub(index.built_at)));
+    row("Refreshed", relative_age(now.saturating_sub(index.refreshed_at)));
+    println!();
+
+    if index.is_stale() {
+        log(
+            "⚠ The index is stale, so searches walk the filesystem. Rebuild it with: vcode index rebuild",
+            LogType::Warning,
+        );
+    } else if let Some(missing) = index_roots(&load_config())
+        .into_iter()
+        .find(|root| root.is_dir() && !index.covers(root))
+    {
+        log(
+            &format!(
+                "⚠ {} isn't indexed, so searches there walk the filesystem. Rebuild with: vcode index rebuild",
+                missing.display()
+            ),
+            LogType::Warning,
+        );
+    }
+}
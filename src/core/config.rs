@@ -1,10 +1,27 @@
 use crate::APP_NAME;
+use crate::core::project::{ConflictStrategy, NameStrategy};
+use crate::core::storage::{FileLock, write_atomic};
+use crate::error::{Error, Result};
+use clap::ValueEnum;
 use dirs;
-use inquire::{Select, Text};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs::{create_dir_all, read_to_string, write};
-use std::path::PathBuf;
+use std::fs::{create_dir_all, read_to_string};
+use std::path::{Path, PathBuf};
+
+/// How an editor process relates to the terminal vcode was run from.
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum EditorMode {
+    /// Start in the background, detached from the terminal (GUI editors).
+    #[default]
+    GuiDetached,
+    /// Replace vcode with the editor in the current terminal, in the
+    /// project directory (nvim, vim, helix, `emacs -nw`).
+    TerminalForeground,
+    /// Start in a new window of the configured terminal emulator.
+    TerminalNewWindow,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct EditorConfig {
@@ -13,6 +30,20 @@ pub struct EditorConfig {
     pub args: Vec<String>,
     #[serde(default)]
     pub reuse_flag: Option<String>,
+    /// Arguments used instead of the project path when opening a file, with
+    /// `{path}`, `{file}`, `{line}` and `{column}` placeholders. `None`
+    /// picks a template from the command name, see [`default_file_args`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_args: Option<Vec<String>>,
+    /// Full argv, program first, with `{path}`, `{name}`, `{file}`,
+    /// `{line}`, `{column}`, `{reuse}` and `{type}` placeholders. When set,
+    /// it replaces `command`/`args`/`file_args` entirely, e.g.
+    /// `["kitty", "-e", "nvim", "{path}"]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<Vec<String>>,
+    /// `None` infers the mode from the command, see [`default_mode`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<EditorMode>,
 }
 
 impl EditorConfig {
@@ -21,6 +52,22 @@ impl EditorConfig {
             command,
             args: Vec::new(),
             reuse_flag: Some("-r".to_string()),
+            file_args: None,
+            template: None,
+            mode: None,
+        }
+    }
+
+    /// A terminal editor. There's no window to reuse, and `-r` means
+    /// something else entirely to vim-likes (swap-file recovery).
+    pub fn terminal(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            args: Vec::new(),
+            reuse_flag: None,
+            file_args: None,
+            template: None,
+            mode: Some(EditorMode::TerminalForeground),
         }
     }
 
@@ -29,25 +76,263 @@ impl EditorConfig {
             command: command.to_string(),
             args: vec!["--no-sandbox".to_string()],
             reuse_flag: Some("-r".to_string()),
+            file_args: None,
+            template: None,
+            mode: None,
         }
     }
+
+    /// The configured mode, or the one inferred from the command.
+    pub fn mode_or_default(&self) -> EditorMode {
+        self.mode
+            .unwrap_or_else(|| default_mode(&self.command, &self.args))
+    }
+
+    /// The configured file template, or the built-in one for this command.
+    pub fn file_args_or_default(&self) -> Vec<String> {
+        self.file_args
+            .clone()
+            .unwrap_or_else(|| default_file_args(&self.command))
+    }
+
+    /// The argv template to render for an open. Editors without an explicit
+    /// `template` get the one their older fields always meant:
+    /// `command args {reuse} {path}`, with `file_args` in place of `{path}`
+    /// when a file was requested.
+    pub fn argv_template(&self, with_file: bool) -> Vec<String> {
+        if let Some(template) = &self.template {
+            return template.clone();
+        }
+        let mut argv = vec![self.command.clone()];
+        argv.extend(self.args.iter().cloned());
+        argv.push("{reuse}".to_string());
+        if with_file {
+            argv.extend(self.file_args_or_default());
+        } else {
+            argv.push("{path}".to_string());
+        }
+        argv
+    }
+}
+
+/// Infers the mode for editors configured without one, so configs written
+/// before modes existed stop launching `nvim` detached from any terminal.
+pub fn default_mode(command: &str, args: &[String]) -> EditorMode {
+    let name = std::path::Path::new(command)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let terminal = matches!(
+        name.as_str(),
+        "nvim" | "vim" | "vi" | "hx" | "helix" | "kak" | "micro" | "nano"
+    ) || (name.starts_with("emacs") && args.iter().any(|a| a == "-nw" || a == "-t"));
+    if terminal {
+        EditorMode::TerminalForeground
+    } else {
+        EditorMode::GuiDetached
+    }
+}
+
+/// `$TERMINAL -e` (given as `env_terminal`), or `x-terminal-emulator -e`
+/// when it's unset or blank.
+fn fallback_terminal(env_terminal: Option<String>) -> Vec<String> {
+    let program = env_terminal
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| "x-terminal-emulator".to_string());
+    vec![program, "-e".to_string()]
+}
+
+/// Built-in file/line templates, keyed on the command's basename so that
+/// configs written before templates existed (and `/usr/bin/nvim`-style
+/// commands) still get the right syntax. Unknown editors just get the file.
+pub fn default_file_args(command: &str) -> Vec<String> {
+    let name = std::path::Path::new(command)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let template: &[&str] = match name.as_str() {
+        "code" | "cursor" | "vscodium" | "codium" => &["{path}", "-g", "{file}:{line}:{column}"],
+        "zed" | "subl" => &["{path}", "{file}:{line}:{column}"],
+        "hx" | "helix" => &["{file}:{line}:{column}"],
+        "nvim" | "vim" | "vi" => &["+{line}", "{file}"],
+        "emacs" | "emacsclient" => &["+{line}:{column}", "{file}"],
+        _ => &["{file}"],
+    };
+    template.iter().map(|s| s.to_string()).collect()
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Config {
-    pub projects_root: String,
+    /// Where projects live, in order. Scans with no path walk all of them
+    /// and `search --fs` searches all of them.
+    #[serde(default)]
+    pub roots: Vec<ProjectRoot>,
+    /// The single root of configs written before `roots` existed; moved
+    /// into `roots` on load and never written back.
+    #[serde(default, rename = "projects_root", skip_serializing)]
+    legacy_projects_root: Option<String>,
     pub default_editor: String,
     #[serde(default)]
     pub editors: HashMap<String, EditorConfig>,
+    /// How `vcode scan` and `search --fs` resolve a name that's already taken.
+    #[serde(default)]
+    pub on_conflict: ConflictStrategy,
+    /// How `vcode scan`, `here` and `add <path>` name what they find.
+    #[serde(default)]
+    pub naming: NameStrategy,
+    /// Terminal emulator argv for `terminal-new-window` editors; the editor's
+    /// own argv is appended. Supports the editor template placeholders.
+    /// `None` uses `$TERMINAL -e`, falling back to `x-terminal-emulator -e`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal: Option<Vec<String>>,
+    /// Project markers checked alongside the built-in ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<MarkerRule>,
+    /// Changes to the built-in list of directories scans never enter.
+    #[serde(default, skip_serializing_if = "SkipDirs::is_empty")]
+    pub skip_dirs: SkipDirs,
+    /// How much a single scan or filesystem search may walk.
+    #[serde(default, skip_serializing_if = "WalkLimits::is_default")]
+    pub walk_limits: WalkLimits,
+}
+
+/// A directory projects live in, and how to treat what's found there.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProjectRoot {
+    pub path: String,
+    /// How many levels below `path` projects sit (1 = immediate children).
+    #[serde(default = "ProjectRoot::default_depth")]
+    pub depth: u32,
+    /// Tags given to every project registered from this root.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Prepended to the names of projects registered from this root, e.g.
+    /// `work/` registers `api` as `work/api`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+}
+
+impl ProjectRoot {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            depth: Self::default_depth(),
+            tags: Vec::new(),
+            prefix: None,
+        }
+    }
+
+    fn default_depth() -> u32 {
+        1
+    }
+
+    /// `name` with this root's prefix.
+    pub fn project_name(&self, name: &str) -> String {
+        format!("{}{}", self.prefix.as_deref().unwrap_or(""), name)
+    }
+}
+
+/// A user-defined project marker: when `pattern` exists in a directory,
+/// it's a project of type `project_type`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MarkerRule {
+    /// A file or directory name (`mix.exs`), a nested path
+    /// (`.github/workflows`), or a name glob (`*.tf`).
+    pub pattern: String,
+    /// Shown as the project type. A built-in name (`Rust`, `Go`, ...) adds
+    /// the marker to that type instead of creating a new one.
+    #[serde(rename = "type")]
+    pub project_type: String,
+}
+
+/// Directory names to skip in addition to, or stop skipping from, the
+/// built-in list.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SkipDirs {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub add: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove: Vec<String>,
+}
+
+impl SkipDirs {
+    pub fn is_empty(&self) -> bool {
+        self.add.is_empty() && self.remove.is_empty()
+    }
+}
+
+/// Budget for one directory walk. A walk that hits either limit stops and
+/// returns what it found so far. `0` means unlimited.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct WalkLimits {
+    /// Directory entries read (files included) before stopping.
+    #[serde(default = "WalkLimits::default_max_entries")]
+    pub max_entries: usize,
+    /// Seconds before stopping.
+    #[serde(default = "WalkLimits::default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl WalkLimits {
+    fn default_max_entries() -> usize {
+        500_000
+    }
+
+    fn default_timeout_secs() -> u64 {
+        10
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for WalkLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: Self::default_max_entries(),
+            timeout_secs: Self::default_timeout_secs(),
+        }
+    }
 }
 
 impl Config {
     pub fn new(projects_root: String, default_editor: String) -> Self {
         Self {
-            projects_root,
+            roots: vec![ProjectRoot::new(projects_root)],
+            legacy_projects_root: None,
             default_editor,
             editors: default_editors(),
+            on_conflict: ConflictStrategy::default(),
+            naming: NameStrategy::default(),
+            terminal: None,
+            markers: Vec::new(),
+            skip_dirs: SkipDirs::default(),
+            walk_limits: WalkLimits::default(),
+        }
+    }
+
+    /// The terminal emulator argv prefix for `terminal-new-window` editors.
+    pub fn terminal_command(&self) -> Vec<String> {
+        if let Some(terminal) = &self.terminal {
+            return terminal.clone();
         }
+        fallback_terminal(std::env::var("TERMINAL").ok())
+    }
+
+    /// The first root, where first-time setup and `config set projects-root`
+    /// point. `None` only if every root was removed by hand.
+    pub fn primary_root(&self) -> Option<&ProjectRoot> {
+        self.roots.first()
+    }
+
+    /// The configured root `path` is, or is inside of (the innermost one
+    /// when roots nest).
+    pub fn root_for(&self, path: &std::path::Path) -> Option<&ProjectRoot> {
+        self.roots
+            .iter()
+            .filter(|root| path.starts_with(&root.path))
+            .max_by_key(|root| root.path.len())
     }
 
     pub fn get_editor(&self, name: &str) -> Option<&EditorConfig> {
@@ -63,84 +348,174 @@ impl Config {
     }
 }
 
-fn default_editors() -> HashMap<String, EditorConfig> {
+/// The editors every new config starts with.
+pub fn default_editors() -> HashMap<String, EditorConfig> {
     let mut editors = HashMap::new();
     editors.insert("code".to_string(), EditorConfig::vscode_like("code"));
     editors.insert("cursor".to_string(), EditorConfig::vscode_like("cursor"));
     editors.insert("vscodium".to_string(), EditorConfig::vscode_like("vscodium"));
     editors.insert("zed".to_string(), EditorConfig::new("zed".to_string()));
-    editors.insert("nvim".to_string(), EditorConfig::new("nvim".to_string()));
-    editors.insert("vim".to_string(), EditorConfig::new("vim".to_string()));
+    editors.insert("nvim".to_string(), EditorConfig::terminal("nvim"));
+    editors.insert("vim".to_string(), EditorConfig::terminal("vim"));
+    editors.insert("helix".to_string(), EditorConfig::terminal("hx"));
     editors.insert("emacs".to_string(), EditorConfig::new("emacs".to_string()));
     editors.insert("sublime".to_string(), EditorConfig::new("subl".to_string()));
     editors
 }
 
-pub fn get_config_path() -> PathBuf {
-    dirs::config_dir()
-        .expect("Could not find config directory")
+pub fn get_config_path() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .ok_or(Error::NoDir("config"))?
         .join(APP_NAME)
-        .join("config.json")
+        .join("config.json"))
 }
 
-pub fn init_config() -> Config {
-    let config_dir = dirs::config_dir()
-        .expect("Could not find config directory")
-        .join(APP_NAME);
-    let config_path = config_dir.join("config.json");
-
-    create_dir_all(&config_dir).expect("Failed to create config directory");
-
-    println!("First time setup!");
-
-    let projects_root =
-        Text::new("Provide a path to the directory that contains all your projects:")
-            .with_default(&dirs::home_dir().unwrap().join("projects").to_string_lossy())
-            .prompt()
-            .expect("Failed to get projects root");
+/// Loads `config.json`. Returns [`Error::ConfigMissing`] when there is none
+/// yet, so the caller can decide whether to run first-time setup.
+pub fn get_config() -> Result<Config> {
+    let (config, legacy) = load_config_file()?;
+    if legacy.is_some() {
+        // Persist the migration right away; `update_config` re-reads under
+        // the lock, so a process that migrated first just gets rewritten.
+        update_config(|_| Ok(()))?;
+    }
+    Ok(config)
+}
 
-    let editors = default_editors();
-    let editor_names: Vec<&str> = editors.keys().map(|s| s.as_str()).collect();
+/// Reads and parses `config.json`. The second value is the file's original
+/// contents when it had to be migrated.
+fn load_config_file() -> Result<(Config, Option<String>)> {
+    let config_path = get_config_path()?;
 
-    let default_editor = Select::new("Choose your default editor:", editor_names)
-        .prompt()
-        .expect("Failed to get editor choice");
+    let config_str = match read_to_string(&config_path) {
+        Ok(s) => s,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(Error::ConfigMissing(config_path));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let (config, migrated) = parse_config(&config_str).map_err(|source| Error::Parse {
+        path: config_path.clone(),
+        source,
+    })?;
+    Ok((config, migrated.then_some(config_str)))
+}
 
-    let default_config = Config {
-        projects_root,
-        default_editor: default_editor.to_string(),
-        editors,
+/// Parses `config.json`, moving a legacy `projects_root` into `roots`. The
+/// flag is `true` when that happened and the file should be rewritten.
+fn parse_config(contents: &str) -> Result<(Config, bool), serde_json::Error> {
+    let mut config: Config = serde_json::from_str(contents)?;
+    let Some(legacy) = config.legacy_projects_root.take() else {
+        return Ok((config, false));
     };
+    if !config.roots.iter().any(|r| r.path == legacy) {
+        config.roots.insert(0, ProjectRoot::new(legacy));
+    }
+    Ok((config, true))
+}
 
-    let config_json =
-        serde_json::to_string_pretty(&default_config).expect("Failed to serialize config");
-    write(&config_path, config_json).expect("Failed to write config file");
+/// Runs one read-modify-write cycle on `config.json`, like
+/// `update_projects` does for the registry: takes the config lock, reads the
+/// current config, applies `f`, and atomically writes the result. If `f`
+/// fails nothing is written. Fails with [`Error::ConfigMissing`] before
+/// first-time setup; use [`write_config`] for that.
+pub fn update_config<T>(f: impl FnOnce(&mut Config) -> Result<T>) -> Result<T> {
+    let config_path = get_config_path()?;
+    let _lock = lock_config(&config_path)?;
+    let (mut config, legacy) = load_config_file()?;
+    let out = f(&mut config)?;
 
-    default_config
+    if let Some(contents) = legacy {
+        // Like the registry migration: keep the original around.
+        write_atomic(&config_path.with_extension("json.v1.bak"), contents.as_bytes())?;
+    }
+    store_config(&config_path, &config)?;
+    Ok(out)
 }
 
-pub fn get_config() -> Config {
-    let config_path = get_config_path();
+/// Writes `config` to `config.json`, replacing whatever is there. Prefer
+/// [`update_config`] for changes; this is for first-time setup.
+pub fn write_config(config: &Config) -> Result<()> {
+    let config_path = get_config_path()?;
+    let _lock = lock_config(&config_path)?;
+    store_config(&config_path, config)
+}
 
-    match read_to_string(&config_path) {
-        Ok(config_str) => serde_json::from_str(&config_str).expect("Failed to parse config json"),
-        Err(_) => init_config(),
+/// Takes the config lock, creating the config directory on first use.
+fn lock_config(config_path: &Path) -> Result<FileLock> {
+    if let Some(dir) = config_path.parent() {
+        create_dir_all(dir)?;
     }
+    Ok(FileLock::acquire(&config_path.with_extension("lock"))?)
 }
 
-pub fn update_config(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
-    let config_path = get_config_path();
+/// Serializes and atomically writes the config. Caller must hold the lock.
+fn store_config(config_path: &Path, config: &Config) -> Result<()> {
     let config_json = serde_json::to_string_pretty(config)?;
-    write(config_path, config_json)?;
+    write_atomic(config_path, config_json.as_bytes())?;
     Ok(())
 }
 
-pub fn reset_config() -> Config {
-    let config_path = get_config_path();
+/// Deletes `config.json`, so the next [`get_config`] reports it missing.
+pub fn reset_config() -> Result<()> {
+    match std::fs::remove_file(get_config_path()?) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Remove existing config
-    let _ = std::fs::remove_file(&config_path);
+    #[test]
+    fn test_default_mode_inference() {
+        let mode = |command: &str, args: &[&str]| {
+            default_mode(command, &args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+        };
+        assert_eq!(mode("nvim", &[]), EditorMode::TerminalForeground);
+        assert_eq!(mode("/usr/bin/hx", &[]), EditorMode::TerminalForeground);
+        assert_eq!(mode("emacs", &["-nw"]), EditorMode::TerminalForeground);
+        assert_eq!(mode("emacs", &[]), EditorMode::GuiDetached);
+        assert_eq!(mode("code", &[]), EditorMode::GuiDetached);
+    }
+
+    #[test]
+    fn test_terminal_command_fallback() {
+        let argv = |v: &[&str]| v.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(fallback_terminal(Some("alacritty".to_string())), argv(&["alacritty", "-e"]));
+        assert_eq!(fallback_terminal(Some("  ".to_string())), argv(&["x-terminal-emulator", "-e"]));
+        assert_eq!(fallback_terminal(None), argv(&["x-terminal-emulator", "-e"]));
 
-    // Run init again
-    init_config()
+        let mut config = Config::new("/code".to_string(), "code".to_string());
+        config.terminal = Some(argv(&["kitty", "-e"]));
+        assert_eq!(config.terminal_command(), argv(&["kitty", "-e"]));
+    }
+
+    #[test]
+    fn test_parse_config_migrates_single_root() {
+        let legacy = r#"{"projects_root": "/home/me/code", "default_editor": "code"}"#;
+        let (config, migrated) = parse_config(legacy).unwrap();
+        assert!(migrated);
+        assert_eq!(config.roots, [ProjectRoot::new("/home/me/code".to_string())]);
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(!json.contains("projects_root"));
+
+        let (reparsed, migrated) = parse_config(&json).unwrap();
+        assert!(!migrated);
+        assert_eq!(reparsed, config);
+    }
+
+    #[test]
+    fn test_root_for_prefers_innermost() {
+        let mut config = Config::new("/src".to_string(), "code".to_string());
+        config.roots.push(ProjectRoot {
+            prefix: Some("oss/".to_string()),
+            ..ProjectRoot::new("/src/oss".to_string())
+        });
+        let root = config.root_for(std::path::Path::new("/src/oss/tokio")).unwrap();
+        assert_eq!(root.project_name("tokio"), "oss/tokio");
+        assert_eq!(config.root_for(std::path::Path::new("/src/app")).unwrap().path, "/src");
+        assert!(config.root_for(std::path::Path::new("/srv/app")).is_none());
+    }
 }
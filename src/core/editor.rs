@@ -1,44 +1,423 @@
-use crate::core::config::{get_config, EditorConfig};
+use crate::core::config::{Config, EditorConfig, EditorMode};
+use crate::error::{Error, Result};
+use crate::scanner::detect_project_type;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
-pub fn open_with_editor(
-    editor: &str,
-    project_path: &str,
-    reuse: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let config = get_config();
+/// A file to focus after opening a project, optionally at a line and column
+/// (`src/main.rs:42:7`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileTarget {
+    pub path: PathBuf,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+}
+
+impl FileTarget {
+    /// Parses `file[:line[:column]]`, resolving a relative `file` against the
+    /// project `root`. Only numeric suffixes are split off, so a file whose
+    /// name genuinely contains a colon still round-trips.
+    pub fn parse(root: &Path, raw: &str) -> Self {
+        let (file, line, column) = split_position(raw);
+        let file = Path::new(file);
+        let path = if file.is_absolute() {
+            file.to_path_buf()
+        } else {
+            root.join(file)
+        };
+        Self { path, line, column }
+    }
+}
+
+fn split_position(raw: &str) -> (&str, Option<u32>, Option<u32>) {
+    let numeric_tail = |s: &str| -> Option<(usize, u32)> {
+        let (head, tail) = s.rsplit_once(':')?;
+        let n = tail.parse().ok()?;
+        Some((head.len(), n))
+    };
 
-    // Try to get editor config, or create a simple one for unknown editors
-    let editor_config = config.get_editor(editor).cloned().unwrap_or_else(|| {
-        EditorConfig::new(editor.to_string())
-    });
+    match numeric_tail(raw) {
+        Some((end, last)) => match numeric_tail(&raw[..end]) {
+            Some((start, line)) if start > 0 => (&raw[..start], Some(line), Some(last)),
+            _ if end > 0 => (&raw[..end], Some(last), None),
+            _ => (raw, None, None),
+        },
+        None => (raw, None, None),
+    }
+}
+
+/// What to open: the project root, the name it's registered under (or the
+/// directory basename for unregistered paths), and optionally a file.
+#[derive(Debug, Clone, Copy)]
+pub struct OpenRequest<'a> {
+    pub path: &'a str,
+    pub name: &'a str,
+    pub reuse: bool,
+    pub file: Option<&'a FileTarget>,
+}
 
-    let mut command = Command::new("setsid");
-    command.arg(&editor_config.command);
+/// Looks up `editor` in `config.editors`, or treats it as a bare command if
+/// it isn't registered there.
+fn editor_config(config: &Config, editor: &str) -> EditorConfig {
+    config
+        .get_editor(editor)
+        .cloned()
+        .unwrap_or_else(|| EditorConfig::new(editor.to_string()))
+}
 
-    // Add configured args
-    for arg in &editor_config.args {
-        command.arg(arg);
+/// The mode `editor` will actually launch in. A terminal-foreground editor
+/// started without a terminal (from a GUI launcher, say) has nothing to take
+/// over, so it gets a new terminal window instead.
+pub fn effective_mode(config: &Config, editor: &str) -> EditorMode {
+    match editor_config(config, editor).mode_or_default() {
+        EditorMode::TerminalForeground if !std::io::stdin().is_terminal() => {
+            EditorMode::TerminalNewWindow
+        }
+        mode => mode,
     }
+}
 
-    // Add reuse flag if requested and available
-    if reuse {
-        if let Some(ref flag) = editor_config.reuse_flag {
-            command.arg(flag);
+/// Launches `request` in `editor`. The argv comes from the editor's
+/// `template` when it has one, otherwise from the implicit
+/// `command args {reuse} {path}` (or `file_args` when opening a file).
+///
+/// In [`EditorMode::TerminalForeground`] this `exec`s the editor on unix and
+/// only returns on failure; callers should do any bookkeeping first (see
+/// [`effective_mode`]).
+pub fn open_with_editor(config: &Config, editor: &str, request: &OpenRequest) -> Result<()> {
+    let mode = effective_mode(config, editor);
+    let argv = launch_argv(config, editor, request, mode)?;
+
+    match mode {
+        EditorMode::TerminalForeground => run_foreground(&argv, request.path),
+        EditorMode::GuiDetached | EditorMode::TerminalNewWindow => {
+            // Editors that only receive a file still see the project as their cwd.
+            Command::new("setsid")
+                .args(&argv)
+                .current_dir(request.path)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()?;
+            Ok(())
         }
     }
+}
 
-    command.arg(project_path);
+/// The command line that opens `request` in `editor` under `mode`: the
+/// editor's argv, behind the terminal command for a new window.
+fn launch_argv(config: &Config, editor: &str, request: &OpenRequest, mode: EditorMode) -> Result<Vec<String>> {
+    let editor_config = editor_config(config, editor);
+    let values = TemplateValues::new(&editor_config, request);
 
-    command
-        .stdin(Stdio::null())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
+    let mut argv = render_template(&editor_config.argv_template(request.file.is_some()), &values);
+    if argv.is_empty() {
+        return Err(Error::InvalidTemplate(editor.to_string()));
+    }
+    if mode == EditorMode::TerminalNewWindow {
+        let mut terminal = render_template(&config.terminal_command(), &values);
+        terminal.append(&mut argv);
+        argv = terminal;
+    }
+    Ok(argv)
+}
 
+/// Replaces the vcode process with the editor, keeping the TTY, so quitting
+/// the editor drops the user back at their shell.
+#[cfg(unix)]
+fn run_foreground(argv: &[String], cwd: &str) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    let err = Command::new(&argv[0])
+        .args(&argv[1..])
+        .current_dir(cwd)
+        .exec();
+    Err(err.into())
+}
+
+#[cfg(not(unix))]
+fn run_foreground(argv: &[String], cwd: &str) -> Result<()> {
+    // No exec off unix: run the editor to completion in this console.
+    Command::new(&argv[0])
+        .args(&argv[1..])
+        .current_dir(cwd)
+        .status()?;
     Ok(())
 }
 
+/// Values for the placeholders an editor template may use. `None` means
+/// "not applicable to this open", which [`render_template`] handles by
+/// dropping the argument that needed it.
+#[derive(Debug, Default)]
+pub struct TemplateValues {
+    pub path: String,
+    pub name: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub reuse: Option<String>,
+    pub project_type: Option<String>,
+}
+
+impl TemplateValues {
+    fn new(editor: &EditorConfig, request: &OpenRequest) -> Self {
+        let reuse = editor.reuse_flag.clone().filter(|_| request.reuse);
+        // Detection touches the filesystem, so only do it when asked for.
+        let wants_type = editor
+            .template
+            .as_ref()
+            .is_some_and(|t| t.iter().any(|a| a.contains("{type}")));
+        let project_type = wants_type
+            .then(|| detect_project_type(Path::new(request.path)))
+            .flatten()
+            .map(|t| t.name().to_lowercase());
+        Self {
+            path: request.path.to_string(),
+            name: request.name.to_string(),
+            file: request.file.map(|f| f.path.to_string_lossy().into_owned()),
+            line: request.file.and_then(|f| f.line),
+            column: request.file.and_then(|f| f.column),
+            reuse,
+            project_type,
+        }
+    }
+}
+
+/// Expands `{path}`, `{name}`, `{file}`, `{line}`, `{column}`, `{reuse}` and
+/// `{type}` in each argument.
+///
+/// Templates are written for the fullest case (`{file}:{line}:{column}`,
+/// `+{line}`, `{reuse}`). When a value is missing, a `:{line}`/`:{column}`
+/// suffix is dropped, and any argument that still needs a missing value is
+/// skipped entirely, so `+{line}` or a lone `{reuse}` vanish instead of
+/// leaving a stray `+` or empty argument behind.
+///
+/// Each argument is expanded in one pass over the template, so a value that
+/// itself contains `{name}` is inserted as is. Values going into the script
+/// of a shell's `-c` (`sh -c 'cd {path} && make'`) are shell-quoted; such
+/// scripts must not quote the placeholders themselves.
+pub fn render_template(template: &[String], values: &TemplateValues) -> Vec<String> {
+    let line = values.line.map(|l| l.to_string());
+    let column = values.column.map(|c| c.to_string());
+    let placeholders: [(&str, Option<&str>); 7] = [
+        ("{path}", Some(&values.path)),
+        ("{name}", Some(&values.name)),
+        ("{file}", values.file.as_deref()),
+        ("{line}", line.as_deref()),
+        ("{column}", column.as_deref()),
+        ("{reuse}", values.reuse.as_deref()),
+        ("{type}", values.project_type.as_deref()),
+    ];
+
+    template
+        .iter()
+        .enumerate()
+        .filter_map(|(i, arg)| {
+            let script = i >= 2 && is_shell_command_flag(&template[i - 1]) && is_shell(&template[i - 2]);
+            expand_arg(arg, &placeholders, script)
+        })
+        .collect()
+}
+
+/// One template argument with its placeholders replaced, or `None` when
+/// one of them has no value.
+fn expand_arg(arg: &str, placeholders: &[(&str, Option<&str>)], quote: bool) -> Option<String> {
+    let mut out = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while let Some(start) = rest.find(['{', ':']) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let optional = rest.starts_with(':');
+        let at = &rest[usize::from(optional)..];
+        match placeholders.iter().find(|(key, _)| at.starts_with(key)) {
+            // `:{line}`/`:{column}` without a value are dropped, not fatal.
+            Some((key, None)) if optional && matches!(*key, "{line}" | "{column}") => {
+                rest = &at[key.len()..];
+            }
+            Some((key, value)) if !optional => {
+                let value = (*value)?;
+                if quote {
+                    out.push_str(&shell_quote(value));
+                } else {
+                    out.push_str(value);
+                }
+                rest = &at[key.len()..];
+            }
+            _ => {
+                out.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+/// Whether `program` is a POSIX-style shell whose `-c` takes a script.
+fn is_shell(program: &str) -> bool {
+    let name = Path::new(program).file_name().and_then(|n| n.to_str()).unwrap_or(program);
+    ["sh", "bash", "zsh", "dash", "ksh", "fish"].contains(&name)
+}
+
+/// `-c`, or combined short flags ending in it (`-lc`).
+fn is_shell_command_flag(arg: &str) -> bool {
+    arg.strip_prefix('-')
+        .is_some_and(|flags| flags.ends_with('c') && flags.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// `value` as a single shell word: untouched when it's plain, otherwise
+/// single-quoted with embedded quotes spliced in as `'\''`.
+fn shell_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./:+,=@%".contains(c));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// Splits a command line typed at a prompt into argv. Whitespace separates
+/// arguments; single or double quotes group them (`sh -c 'cd {path} && make'`).
+pub fn split_command_line(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            None if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
 pub fn is_vscode_like_editor(editor: &str) -> bool {
     ["cursor", "code", "vscodium"].contains(&editor)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_file_target_parse() {
+        let root = Path::new("/work/api");
+        let t = FileTarget::parse(root, "src/main.rs:42:7");
+        assert_eq!(t.path, PathBuf::from("/work/api/src/main.rs"));
+        assert_eq!((t.line, t.column), (Some(42), Some(7)));
+
+        let t = FileTarget::parse(root, "src/main.rs:42");
+        assert_eq!((t.line, t.column), (Some(42), None));
+
+        let t = FileTarget::parse(root, "/etc/hosts");
+        assert_eq!(t.path, PathBuf::from("/etc/hosts"));
+        assert_eq!(t.line, None);
+
+        // Non-numeric suffixes are part of the name.
+        let t = FileTarget::parse(root, "notes:todo");
+        assert_eq!(t.path, PathBuf::from("/work/api/notes:todo"));
+    }
+
+    fn values(file: Option<&str>, line: Option<u32>) -> TemplateValues {
+        TemplateValues {
+            path: "/p".to_string(),
+            name: "api".to_string(),
+            file: file.map(String::from),
+            line,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_template_drops_missing_values() {
+        let with_line = values(Some("/p/a.rs"), Some(3));
+        let code = template(&["{path}", "-g", "{file}:{line}:{column}"]);
+        assert_eq!(render_template(&code, &with_line), ["/p", "-g", "/p/a.rs:3"]);
+
+        let nvim = template(&["nvim", "+{line}", "{file}", "{reuse}"]);
+        assert_eq!(render_template(&nvim, &with_line), ["nvim", "+3", "/p/a.rs"]);
+        assert_eq!(render_template(&nvim, &values(Some("/p/a.rs"), None)), ["nvim", "/p/a.rs"]);
+    }
+
+    #[test]
+    fn test_render_template_wrapper() {
+        let kitty = template(&["kitty", "--title", "{name}", "-e", "nvim", "{path}"]);
+        assert_eq!(
+            render_template(&kitty, &values(None, None)),
+            ["kitty", "--title", "api", "-e", "nvim", "/p"]
+        );
+    }
+
+    #[test]
+    fn test_render_template_single_pass_and_quoting() {
+        let tricky = TemplateValues {
+            path: "/p/it's {name}".to_string(),
+            name: "api".to_string(),
+            ..Default::default()
+        };
+        let code = template(&["code", "{path}"]);
+        assert_eq!(render_template(&code, &tricky), ["code", "/p/it's {name}"]);
+
+        let dev = template(&["sh", "-c", "cd {path} && make {name}"]);
+        assert_eq!(
+            render_template(&dev, &tricky),
+            ["sh", "-c", r"cd '/p/it'\''s {name}' && make api"]
+        );
+        // Only a shell's script is quoted.
+        let echo = template(&["echo", "-c", "{path}"]);
+        assert_eq!(render_template(&echo, &tricky)[2], "/p/it's {name}");
+    }
+
+    #[test]
+    fn test_launch_argv_new_window() {
+        let mut config = Config::new("/code".to_string(), "code".to_string());
+        config.terminal = Some(template(&["kitty", "--directory", "{path}", "-e"]));
+        let request = OpenRequest {
+            path: "/p",
+            name: "api",
+            reuse: false,
+            file: None,
+        };
+        assert_eq!(
+            launch_argv(&config, "nvim", &request, EditorMode::TerminalNewWindow).unwrap(),
+            ["kitty", "--directory", "/p", "-e", "nvim", "/p"]
+        );
+        assert_eq!(launch_argv(&config, "nvim", &request, EditorMode::TerminalForeground).unwrap(), ["nvim", "/p"]);
+    }
+
+    #[test]
+    fn test_split_command_line() {
+        assert_eq!(
+            split_command_line(r#"sh -c 'cd {path} && make'  "a b""#),
+            ["sh", "-c", "cd {path} && make", "a b"]
+        );
+        assert_eq!(split_command_line("  "), Vec::<String>::new());
+        assert_eq!(split_command_line("x ''"), ["x", ""]);
+    }
+}
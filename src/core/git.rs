@@ -0,0 +1,262 @@
+//! Git metadata read straight from `.git`
+//!
+//! vcode only needs a few facts about a repository (where its git directory
+//! is, what `origin` points at), so it reads them from disk instead of
+//! spawning `git` for every project.
+//!
+//! Working-tree status is the exception: telling a dirty tree from a clean
+//! one means comparing it against the index, which only git does reliably.
+//! [`repo_statuses`] runs `git status` for many repositories at once so a
+//! registry of hundreds still answers in about the time of the slowest one.
+
+use serde::Serialize;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The git directory of the repository whose work tree is `dir`: `.git`
+/// itself, or where a `.git` file (worktrees, submodules) points.
+pub fn git_dir(dir: &Path) -> Option<PathBuf> {
+    let dot_git = dir.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let pointer = read_to_string(&dot_git).ok()?;
+    let target = pointer.strip_prefix("gitdir:")?.trim();
+    Some(dir.join(target))
+}
+
+/// The URL of `dir`'s `origin` remote, or of its first remote when there is
+/// no `origin`. `None` for directories that aren't repositories or have no
+/// remotes.
+pub fn origin_url(dir: &Path) -> Option<String> {
+    let git_dir = git_dir(dir)?;
+    // A linked worktree keeps its config in the main repository.
+    let common = read_to_string(git_dir.join("commondir"))
+        .map(|c| git_dir.join(c.trim()))
+        .unwrap_or(git_dir);
+    let config = read_to_string(common.join("config")).ok()?;
+    let remotes = parse_remotes(&config);
+    remotes
+        .iter()
+        .find(|(name, _)| name == "origin")
+        .or(remotes.first())
+        .map(|(_, url)| url.clone())
+}
+
+/// `(name, url)` of each `[remote "name"]` section in a git config file.
+fn parse_remotes(config: &str) -> Vec<(String, String)> {
+    let mut remotes = Vec::new();
+    let mut current: Option<String> = None;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            current = line
+                .strip_prefix("[remote \"")
+                .and_then(|rest| rest.strip_suffix("\"]"))
+                .map(str::to_string);
+            continue;
+        }
+        let Some(name) = &current else { continue };
+        if let Some((key, value)) = line.split_once('=')
+            && key.trim() == "url"
+            && !remotes.iter().any(|(n, _)| n == name)
+        {
+            remotes.push((name.clone(), value.trim().to_string()));
+        }
+    }
+    remotes
+}
+
+/// `url` reduced to `host/owner/repo`, so the HTTPS and SSH forms of one
+/// remote compare equal.
+pub fn normalize_remote(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest.to_string(),
+        // scp-like `git@host:owner/repo`
+        None => url.replacen(':', "/", 1),
+    };
+    let rest = rest.rsplit_once('@').map_or(rest.as_str(), |(_, r)| r);
+    // Drop a port, as in `ssh://git@host:22/owner/repo`.
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let host = host.split(':').next().unwrap_or(host).to_lowercase();
+    format!("{}/{}", host, path)
+}
+
+/// `owner/repo` (the last two path segments) of a remote URL.
+pub fn remote_repo(url: &str) -> Option<String> {
+    let normalized = normalize_remote(url);
+    let segments: Vec<&str> = normalized.split('/').skip(1).filter(|s| !s.is_empty()).collect();
+    match segments.as_slice() {
+        [] => None,
+        [repo] => Some(repo.to_string()),
+        [.., owner, repo] => Some(format!("{}/{}", owner, repo)),
+    }
+}
+
+/// Whether two remote URLs name the same repository.
+pub fn same_remote(a: &str, b: &str) -> bool {
+    normalize_remote(a) == normalize_remote(b)
+}
+
+/// Upper bound on concurrent `git` processes; status is mostly disk-bound,
+/// so more than this only makes the repositories compete.
+const MAX_GIT_JOBS: usize = 16;
+
+/// What `vcode status` and `vcode list --git` show about a repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RepoStatus {
+    /// The checked-out branch; `None` when HEAD is detached.
+    pub branch: Option<String>,
+    /// The branch's upstream, e.g. `origin/main`.
+    pub upstream: Option<String>,
+    /// Commits on the branch that its upstream doesn't have.
+    pub ahead: u32,
+    /// Commits on the upstream that the branch doesn't have.
+    pub behind: u32,
+    /// Changed, staged, conflicted and untracked files.
+    pub changes: usize,
+    /// Unix time of HEAD's commit; `None` before the first commit.
+    pub last_commit: Option<u64>,
+}
+
+impl RepoStatus {
+    /// Uncommitted changes, or commits not yet pushed or pulled.
+    pub fn needs_attention(&self) -> bool {
+        self.changes > 0 || self.ahead > 0 || self.behind > 0
+    }
+}
+
+/// The status of the repository whose work tree is `dir`, or `None` when
+/// it isn't one or `git` can't be run.
+pub fn repo_status(dir: &Path) -> Option<RepoStatus> {
+    git_dir(dir)?;
+    // `--no-optional-locks` keeps status from refreshing the index, which
+    // would race an editor or a commit running in that repository.
+    let status = git_output(dir, &["--no-optional-locks", "status", "--porcelain=v2", "--branch"])?;
+    let mut repo = parse_status(&status);
+    repo.last_commit = git_output(dir, &["log", "-1", "--format=%ct"]).and_then(|t| t.trim().parse().ok());
+    Some(repo)
+}
+
+/// [`repo_status`] for each of `dirs`, in order, with up to
+/// [`MAX_GIT_JOBS`] repositories queried at a time.
+pub fn repo_statuses<P: AsRef<Path> + Sync>(dirs: &[P]) -> Vec<Option<RepoStatus>> {
+    let jobs = std::thread::available_parallelism()
+        .map_or(4, |n| n.get() * 2)
+        .clamp(1, MAX_GIT_JOBS)
+        .min(dirs.len());
+    let next = AtomicUsize::new(0);
+    let mut statuses = vec![None; dirs.len()];
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(dir) = dirs.get(i) else { break };
+                        done.push((i, repo_status(dir.as_ref())));
+                    }
+                    done
+                })
+            })
+            .collect();
+        for worker in workers {
+            let done = worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            for (i, status) in done {
+                statuses[i] = status;
+            }
+        }
+    });
+    statuses
+}
+
+/// Stdout of `git -C dir <args>`, or `None` if it fails.
+fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Branch, upstream and change count from `git status --porcelain=v2
+/// --branch` output.
+fn parse_status(output: &str) -> RepoStatus {
+    let mut repo = RepoStatus::default();
+    for line in output.lines() {
+        let Some(header) = line.strip_prefix("# ") else {
+            repo.changes += 1;
+            continue;
+        };
+        let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+        match key {
+            "branch.head" if value != "(detached)" => repo.branch = Some(value.to_string()),
+            "branch.upstream" => repo.upstream = Some(value.to_string()),
+            "branch.ab" => {
+                for count in value.split_whitespace() {
+                    let n = count.get(1..).and_then(|n| n.parse().ok()).unwrap_or(0);
+                    if count.starts_with('+') {
+                        repo.ahead = n;
+                    } else {
+                        repo.behind = n;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    repo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_origin_url_prefers_origin() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".git")).unwrap();
+        fs::write(
+            temp.path().join(".git/config"),
+            "[core]\n\tbare = false\n[remote \"upstream\"]\n\turl = https://github.com/up/repo.git\n\
+             [remote \"origin\"]\n\turl = git@github.com:me/repo.git\n\tfetch = +refs/heads/*:refs/remotes/origin/*\n",
+        )
+        .unwrap();
+        assert_eq!(origin_url(temp.path()).as_deref(), Some("git@github.com:me/repo.git"));
+        assert_eq!(origin_url(&temp.path().join("nope")), None);
+    }
+
+    #[test]
+    fn test_same_remote_across_url_forms() {
+        assert!(same_remote("git@github.com:acme/web.git", "https://github.com/acme/web"));
+        assert!(same_remote("ssh://git@GitHub.com:22/acme/web.git", "https://github.com/acme/web/"));
+        assert!(!same_remote("git@github.com:acme/web.git", "git@github.com:acme/api.git"));
+        assert_eq!(remote_repo("git@gitlab.com:group/sub/web.git").as_deref(), Some("sub/web"));
+        assert_eq!(remote_repo("https://example.com/").as_deref(), None);
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let output = "# branch.oid 1f2e3d\n# branch.head main\n# branch.upstream origin/main\n\
+                      # branch.ab +2 -1\n1 .M N... 100644 100644 100644 aa bb src/lib.rs\n? notes.txt\n";
+        let repo = parse_status(output);
+        assert_eq!(repo.branch.as_deref(), Some("main"));
+        assert_eq!(repo.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((repo.ahead, repo.behind, repo.changes), (2, 1, 2));
+        assert!(repo.needs_attention());
+
+        let clean = parse_status("# branch.oid 1f2e3d\n# branch.head (detached)\n");
+        assert_eq!(clean.branch, None);
+        assert!(!clean.needs_attention());
+    }
+}
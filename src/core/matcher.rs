@@ -0,0 +1,322 @@
+//! Fuzzy matching and ranking of projects
+//!
+//! An fzf-style subsequence matcher: every query character must appear in the
+//! candidate in order, and the alignment that earns the most points wins.
+//! Points come from landing on word boundaries (start of string, after `-`,
+//! `_`, `/`, `.` or a space), camelCase humps and consecutive runs; gaps
+//! between matched characters cost a little.
+//!
+//! On top of the raw score, every hit is put in a [`MatchTier`] so that exact
+//! and prefix hits on the project name always outrank looser matches, and
+//! name matches always outrank path-only matches.
+
+use crate::core::project::{ProjectEntry, now_secs};
+use std::collections::HashMap;
+
+const SCORE_MATCH: i64 = 16;
+const SCORE_GAP_START: i64 = 3;
+const SCORE_GAP_EXTENSION: i64 = 1;
+const BONUS_BOUNDARY: i64 = SCORE_MATCH / 2;
+const BONUS_CAMEL: i64 = BONUS_BOUNDARY - SCORE_GAP_EXTENSION;
+const BONUS_CONSECUTIVE: i64 = SCORE_GAP_START + SCORE_GAP_EXTENSION;
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+/// Frecency can lift a project past slightly better-scoring matches within
+/// the same tier, but is capped so a heavily used project can't bury a much
+/// closer match.
+const FRECENCY_BONUS_CAP: f64 = 48.0;
+
+/// How a query matched, strongest first. Ranking compares tiers before
+/// scores, so e.g. a prefix hit always beats the best subsequence hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchTier {
+    /// The project name equals the query (case-insensitive).
+    Exact,
+    /// The project name starts with the query.
+    Prefix,
+    /// The query is a subsequence of the project name.
+    Name,
+    /// The query only matched a segment of the project path.
+    Path,
+}
+
+/// A ranked hit from [`rank_projects`].
+#[derive(Debug, Clone)]
+pub struct ProjectMatch {
+    pub name: String,
+    pub entry: ProjectEntry,
+    pub tier: MatchTier,
+    pub score: i64,
+}
+
+/// Scores `query` as a case-insensitive subsequence of `candidate`. Returns
+/// `None` when some query character can't be matched in order. An empty
+/// query matches everything with a score of zero.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let original: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    if query.len() > lower.len() {
+        return None;
+    }
+
+    let bonuses: Vec<i64> = (0..original.len())
+        .map(|j| position_bonus(&original, j))
+        .collect();
+
+    // best[j]: best score with query[..=i] aligned and query[i] on lower[j].
+    // Rows are rolled since each one only looks at the previous.
+    let mut prev: Vec<Option<i64>> = vec![None; lower.len()];
+    for (i, &qc) in query.iter().enumerate() {
+        let mut cur: Vec<Option<i64>> = vec![None; lower.len()];
+        // Best predecessor that leaves a gap of at least one character before j.
+        let mut gapped: Option<i64> = None;
+
+        for j in 0..lower.len() {
+            if i > 0 && j >= 2 {
+                let extended = gapped.map(|g| g - SCORE_GAP_EXTENSION);
+                let started = prev[j - 2].map(|p| p - SCORE_GAP_START);
+                gapped = extended.max(started);
+            }
+            if lower[j] != qc {
+                continue;
+            }
+
+            cur[j] = if i == 0 {
+                Some(SCORE_MATCH + bonuses[j] * BONUS_FIRST_CHAR_MULTIPLIER)
+            } else {
+                let consecutive = if j >= 1 {
+                    prev[j - 1].map(|p| p + BONUS_CONSECUTIVE)
+                } else {
+                    None
+                };
+                consecutive
+                    .max(gapped)
+                    .map(|p| p + SCORE_MATCH + bonuses[j])
+            };
+        }
+        prev = cur;
+    }
+
+    prev.into_iter().flatten().max()
+}
+
+/// Scores a project against `query`, returning the tier it matched in and
+/// the score within that tier. The name is tried first; the path is only
+/// consulted when the name doesn't match. Path matching is per segment, so
+/// `aps` won't match `/home/alice/projects/svc` by scattering across
+/// directories, unless the query itself contains a `/`.
+pub fn score_project(query: &str, name: &str, path: &str) -> Option<(MatchTier, i64)> {
+    let q = query.to_lowercase();
+    if q.is_empty() {
+        return Some((MatchTier::Name, 0));
+    }
+
+    let name_lower = name.to_lowercase();
+    if name_lower == q {
+        return Some((MatchTier::Exact, 0));
+    }
+    if name_lower.starts_with(&q) {
+        // Shorter names are closer to exact.
+        return Some((MatchTier::Prefix, -(name_lower.len() as i64)));
+    }
+    if let Some(score) = fuzzy_score(&q, name) {
+        return Some((MatchTier::Name, score));
+    }
+
+    let path_score = if q.contains('/') {
+        fuzzy_score(&q, path)
+    } else {
+        path.split('/')
+            .filter(|seg| !seg.is_empty())
+            .filter_map(|seg| fuzzy_score(&q, seg))
+            .max()
+    };
+    path_score.map(|s| (MatchTier::Path, s))
+}
+
+/// Matches and ranks all projects against `query`. Within a tier, hits are
+/// ordered by match score plus a capped frecency bonus, then by shorter name
+/// and alphabetically, so results are deterministic.
+pub fn rank_projects(projects: &HashMap<String, ProjectEntry>, query: &str) -> Vec<ProjectMatch> {
+    let now = now_secs();
+    let mut out: Vec<(ProjectMatch, i64)> = projects
+        .iter()
+        .filter_map(|(name, entry)| {
+            let (tier, score) = score_project(query, name, &entry.path)?;
+            let boost = entry.frecency(now).min(FRECENCY_BONUS_CAP) as i64;
+            Some((
+                ProjectMatch {
+                    name: name.clone(),
+                    entry: entry.clone(),
+                    tier,
+                    score,
+                },
+                score + boost,
+            ))
+        })
+        .collect();
+
+    out.sort_by(|(a, ka), (b, kb)| {
+        a.tier
+            .cmp(&b.tier)
+            .then_with(|| kb.cmp(ka))
+            .then_with(|| a.name.len().cmp(&b.name.len()))
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    out.into_iter().map(|(m, _)| m).collect()
+}
+
+/// The single hit a non-interactive caller can act on without asking: the
+/// top match when it is the only one in the best tier. `None` means the
+/// result is empty or ambiguous.
+pub fn unambiguous(matches: &[ProjectMatch]) -> Option<&ProjectMatch> {
+    let first = matches.first()?;
+    let same_tier = matches.iter().filter(|m| m.tier == first.tier).count();
+    if same_tier == 1 { Some(first) } else { None }
+}
+
+/// Flattens [`score_project`] into the single number an interactive picker
+/// sorts by (higher is better), for use from an inquire `Scorer`. Tier
+/// dominates, then score; `idx` is subtracted last so ties keep the caller's
+/// order (inquire sorts unstably, and an empty filter ties everything).
+pub fn picker_score(query: &str, name: &str, path: &str, idx: usize) -> Option<i64> {
+    const TIER_WEIGHT: i64 = 1 << 40;
+    const SCORE_WEIGHT: i64 = 1 << 20;
+    let (tier, score) = score_project(query, name, path)?;
+    let tier_rank = MatchTier::Path as i64 - tier as i64;
+    Some(tier_rank * TIER_WEIGHT + score * SCORE_WEIGHT - idx as i64)
+}
+
+/// Bonus for a match landing on `chars[j]`, based on what precedes it.
+fn position_bonus(chars: &[char], j: usize) -> i64 {
+    if j == 0 {
+        return BONUS_BOUNDARY;
+    }
+    let prev = chars[j - 1];
+    let cur = chars[j];
+    if matches!(prev, '-' | '_' | '/' | '.' | ' ' | ':') {
+        BONUS_BOUNDARY
+    } else if (prev.is_lowercase() && cur.is_uppercase())
+        || (!prev.is_ascii_digit() && cur.is_ascii_digit())
+    {
+        BONUS_CAMEL
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str) -> ProjectEntry {
+        ProjectEntry {
+            path: path.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_subsequence_matches() {
+        assert!(fuzzy_score("aps", "api-service").is_some());
+        assert!(fuzzy_score("APS", "api-service").is_some());
+        assert!(fuzzy_score("spa", "api-service").is_none());
+        assert!(fuzzy_score("apix", "api").is_none());
+    }
+
+    #[test]
+    fn test_boundaries_beat_scattered() {
+        let boundary = fuzzy_score("as", "api-service").unwrap();
+        let scattered = fuzzy_score("as", "bananas").unwrap();
+        assert!(boundary > scattered);
+    }
+
+    #[test]
+    fn test_camel_case_humps() {
+        let camel = fuzzy_score("ms", "myService").unwrap();
+        let flat = fuzzy_score("ms", "mymess").unwrap();
+        assert!(camel > flat);
+    }
+
+    #[test]
+    fn test_consecutive_beats_gapped() {
+        let run = fuzzy_score("api", "xapi").unwrap();
+        let gapped = fuzzy_score("api", "xaxpxi").unwrap();
+        assert!(run > gapped);
+    }
+
+    #[test]
+    fn test_tiers() {
+        assert_eq!(score_project("api", "API", "/w/API").unwrap().0, MatchTier::Exact);
+        assert_eq!(score_project("api", "api-service", "/w/x").unwrap().0, MatchTier::Prefix);
+        assert_eq!(score_project("aps", "api-service", "/w/x").unwrap().0, MatchTier::Name);
+        assert_eq!(score_project("client", "web", "/work/client-a/web").unwrap().0, MatchTier::Path);
+        assert!(score_project("zzz", "web", "/work/web").is_none());
+    }
+
+    #[test]
+    fn test_path_segments_do_not_scatter() {
+        assert!(score_project("aps", "svc", "/home/alice/projects/svc").is_none());
+        assert!(score_project("alice/svc", "x", "/home/alice/projects/svc").is_some());
+    }
+
+    #[test]
+    fn test_rank_exact_and_prefix_win() {
+        let mut projects = HashMap::new();
+        projects.insert("api".to_string(), entry("/w/api"));
+        projects.insert("api-service".to_string(), entry("/w/api-service"));
+        projects.insert("rapid".to_string(), entry("/w/rapid"));
+        projects.insert("a-p-i".to_string(), entry("/w/a-p-i"));
+
+        let ranked = rank_projects(&projects, "api");
+        let names: Vec<&str> = ranked.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names[0], "api");
+        assert_eq!(names[1], "api-service");
+        assert_eq!(ranked.len(), 4);
+    }
+
+    #[test]
+    fn test_rank_frecency_breaks_near_ties() {
+        let mut projects = HashMap::new();
+        projects.insert("web-app".to_string(), entry("/w/web-app"));
+        let mut busy = entry("/w/web-api");
+        busy.open_count = 10;
+        busy.last_opened = Some(now_secs());
+        projects.insert("web-api".to_string(), busy);
+
+        let ranked = rank_projects(&projects, "wa");
+        assert_eq!(ranked[0].name, "web-api");
+    }
+
+    #[test]
+    fn test_picker_score_keeps_order_for_empty_query() {
+        let first = picker_score("", "zeta", "/w/zeta", 0).unwrap();
+        let second = picker_score("", "alpha", "/w/alpha", 1).unwrap();
+        assert!(first > second);
+
+        let prefix = picker_score("we", "web", "/w/web", 5).unwrap();
+        let fuzzy = picker_score("we", "tower-e", "/w/tower-e", 0).unwrap();
+        assert!(prefix > fuzzy);
+    }
+
+    #[test]
+    fn test_unambiguous() {
+        let mut projects = HashMap::new();
+        projects.insert("api-service".to_string(), entry("/w/api-service"));
+        projects.insert("rapid".to_string(), entry("/w/rapid"));
+        let ranked = rank_projects(&projects, "api");
+        assert_eq!(unambiguous(&ranked).unwrap().name, "api-service");
+
+        projects.insert("api-gateway".to_string(), entry("/w/api-gateway"));
+        let ranked = rank_projects(&projects, "api");
+        assert!(unambiguous(&ranked).is_none());
+    }
+}
@@ -4,17 +4,39 @@
 //! - Configuration management (config.rs)
 //! - Project CRUD operations (project.rs)
 //! - Editor integration (editor.rs)
+//! - Fuzzy matching and ranking (matcher.rs)
 //! - Clipboard integration (clipboard.rs)
+//! - Atomic, locked file persistence (storage.rs)
+//! - The queue of projects awaiting review (pending.rs)
+//! - Git metadata read from `.git` (git.rs)
 
 pub mod clipboard;
 pub mod config;
 pub mod editor;
+pub mod git;
+pub mod matcher;
+pub mod pending;
 pub mod project;
+pub mod storage;
 
 // Re-export commonly used items
-pub use config::{Config, EditorConfig, get_config, get_config_path, init_config, reset_config, update_config};
-pub use editor::{is_vscode_like_editor, open_with_editor};
+pub use config::{
+    Config, EditorConfig, EditorMode, MarkerRule, ProjectRoot, SkipDirs, WalkLimits, default_editors,
+    default_file_args, default_mode, get_config, get_config_path, reset_config, update_config, write_config,
+};
+pub use editor::{
+    FileTarget, OpenRequest, TemplateValues, effective_mode, is_vscode_like_editor, open_with_editor,
+    render_template, split_command_line,
+};
+pub use matcher::{
+    MatchTier, ProjectMatch, fuzzy_score, picker_score, rank_projects, score_project, unambiguous,
+};
+pub use pending::{PendingProject, get_pending, get_pending_path, queue_pending, remove_pending};
 pub use project::{
-    delete_project, get_data_path, get_projects, path_basename, rename_project, reset_projects,
-    resolve_path, set_project, set_project_validated, try_resolve_existing_dir, write_projects,
+    ConflictStrategy, Insertion, NameStrategy, ProjectEntry, delete_project, get_data_path, get_index_path,
+    get_projects,
+    insert_project, normalize_tags, path_basename, path_str, record_open, relocate_project,
+    rename_project, reset_projects, resolve_path, set_project, set_project_validated, tag_counts,
+    tag_project, try_resolve_existing_dir, untag_project, update_projects, validate_project_dir,
+    write_projects,
 };
@@ -0,0 +1,77 @@
+//! Projects waiting for review
+//!
+//! `vcode watch --review` doesn't register what it finds; it queues the
+//! directories in `pending.json` (next to `projects.json`) for
+//! `vcode scan --pending` to present later. The queue is read-modify-written
+//! under its own lock, like the registry.
+
+use super::project::{data_dir, ensure_data_dir, now_secs};
+use super::storage::{FileLock, write_atomic};
+use crate::error::{Error, Result};
+use crate::scanner::NameSource;
+use serde::{Deserialize, Serialize};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+/// A directory queued for review.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PendingProject {
+    pub name: String,
+    pub path: PathBuf,
+    /// Tags from the project root it was found under.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Where `name` came from, for the review list.
+    #[serde(default)]
+    pub name_source: NameSource,
+    /// Unix timestamp (seconds) of when it was queued.
+    pub queued_at: u64,
+}
+
+pub fn get_pending_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join("pending.json"))
+}
+
+/// The queued projects, oldest first. A missing file is an empty queue.
+pub fn get_pending() -> Result<Vec<PendingProject>> {
+    let path = get_pending_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = read_to_string(&path)?;
+    serde_json::from_str(&contents).map_err(|source| Error::Parse { path, source })
+}
+
+/// Queues `path` under `name` (which came from `name_source`), to be
+/// registered with `tags`. Returns false when it was already queued.
+pub fn queue_pending(name: &str, name_source: NameSource, path: &Path, tags: &[String]) -> Result<bool> {
+    update_pending(|queue| {
+        if queue.iter().any(|p| p.path == path) {
+            return false;
+        }
+        queue.push(PendingProject {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+            tags: tags.to_vec(),
+            name_source,
+            queued_at: now_secs(),
+        });
+        true
+    })
+}
+
+/// Drops `paths` from the queue once they've been reviewed.
+pub fn remove_pending(paths: &[PathBuf]) -> Result<()> {
+    update_pending(|queue| queue.retain(|p| !paths.contains(&p.path)))
+}
+
+fn update_pending<T>(f: impl FnOnce(&mut Vec<PendingProject>) -> T) -> Result<T> {
+    ensure_data_dir()?;
+    let path = get_pending_path()?;
+    let _lock = FileLock::acquire(&path.with_extension("lock"))?;
+    let mut queue = get_pending()?;
+    let out = f(&mut queue);
+    let json = serde_json::to_string_pretty(&queue).map_err(Error::Serialize)?;
+    write_atomic(&path, json.as_bytes())?;
+    Ok(out)
+}
@@ -1,74 +1,526 @@
 use crate::APP_NAME;
+use crate::core::git;
+use crate::core::storage::{FileLock, write_atomic};
+use crate::error::{Error, Result};
+use clap::ValueEnum;
 use dirs;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs::{create_dir_all, read_to_string, write};
+use std::fs::{create_dir_all, read_to_string};
 use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-pub fn get_data_path() -> PathBuf {
-    dirs::data_dir()
-        .expect("Could not find data directory")
-        .join(APP_NAME)
-        .join("projects.json")
+/// Current on-disk schema version of `projects.json`. Version 1 was the
+/// original flat `{ "name": "path" }` map; it is migrated on first read.
+pub const REGISTRY_VERSION: u32 = 2;
+
+/// Everything vcode knows about a registered project. Only `path` is
+/// required; the rest is filled in as the project gets used, and defaults
+/// keep older or hand-edited registries loading cleanly.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ProjectEntry {
+    pub path: String,
+    /// Unix timestamp (seconds) of when the project was registered. `None`
+    /// for entries migrated from the flat format, where it was never recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_at: Option<u64>,
+    /// Unix timestamp (seconds) of the last successful open.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_opened: Option<u64>,
+    #[serde(default)]
+    pub open_count: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Editor key to use for this project instead of the configured default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// Unix timestamp (seconds) of when `vcode watch` saw the directory get
+    /// deleted or moved out of the watched tree. Cleared by `vcode update`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missing_since: Option<u64>,
+    /// URL of the directory's git remote (`origin`) when it was registered
+    /// or last moved, so a clone that moves can be recognised by it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
 }
 
-pub fn get_projects() -> HashMap<String, String> {
-    let data_dir = dirs::data_dir()
-        .expect("Could not find data directory")
-        .join(APP_NAME);
-    let data_path = data_dir.join("projects.json");
+impl ProjectEntry {
+    /// A freshly registered project, stamped with the current time.
+    pub fn new(path: String) -> Self {
+        Self {
+            remote: git::origin_url(std::path::Path::new(&path)),
+            path,
+            added_at: Some(now_secs()),
+            ..Default::default()
+        }
+    }
+
+    /// Points the entry at `path`, where its directory now is.
+    pub fn move_to(&mut self, path: String) {
+        if let Some(remote) = git::origin_url(std::path::Path::new(&path)) {
+            self.remote = Some(remote);
+        }
+        self.path = path;
+        self.missing_since = None;
+    }
+
+    /// zoxide-style frecency: the open count weighted by how recently the
+    /// project was last opened. Never-opened projects score zero, so they
+    /// fall back to whatever tiebreak the caller uses.
+    pub fn frecency(&self, now: u64) -> f64 {
+        let Some(last) = self.last_opened else {
+            return 0.0;
+        };
+        let age = now.saturating_sub(last);
+        let weight = if age < 60 * 60 {
+            4.0
+        } else if age < 24 * 60 * 60 {
+            2.0
+        } else if age < 7 * 24 * 60 * 60 {
+            0.5
+        } else {
+            0.25
+        };
+        self.open_count as f64 * weight
+    }
+
+    /// True when the project carries every tag in `tags` (already
+    /// normalized). An empty filter matches everything.
+    pub fn has_all_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|t| self.tags.contains(t))
+    }
+
+    /// Adds normalized `tags`, keeping the list sorted and free of
+    /// duplicates. Returns the ones that weren't already there.
+    pub fn add_tags(&mut self, tags: &[String]) -> Vec<String> {
+        let added: Vec<String> = tags
+            .iter()
+            .filter(|t| !self.tags.contains(t))
+            .cloned()
+            .collect();
+        self.tags.extend(added.iter().cloned());
+        self.tags.sort();
+        self.tags.dedup();
+        added
+    }
 
-    if !data_dir.exists() {
-        create_dir_all(&data_dir).expect("Failed to create data directory");
+    /// Removes normalized `tags`. Returns the ones that were present.
+    pub fn remove_tags(&mut self, tags: &[String]) -> Vec<String> {
+        let removed: Vec<String> = self
+            .tags
+            .iter()
+            .filter(|t| tags.contains(t))
+            .cloned()
+            .collect();
+        self.tags.retain(|t| !tags.contains(t));
+        removed
     }
+}
+
+/// The versioned wrapper that is actually serialized to `projects.json`.
+#[derive(Serialize, Deserialize, Debug)]
+struct RegistryFile {
+    version: u32,
+    projects: HashMap<String, ProjectEntry>,
+}
+
+/// Either shape `projects.json` may have on disk. `untagged` tries the
+/// versioned form first, so a legacy map that happens to contain a project
+/// named `version` still falls through to `Legacy` (its value is a string).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RegistryOnDisk {
+    Versioned(RegistryFile),
+    Legacy(HashMap<String, String>),
+}
 
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub(crate) fn data_dir() -> Result<PathBuf> {
+    Ok(dirs::data_dir().ok_or(Error::NoDir("data"))?.join(APP_NAME))
+}
+
+pub fn get_data_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join("projects.json"))
+}
+
+/// `index.json`, the directory index used by filesystem search.
+pub fn get_index_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join("index.json"))
+}
+
+pub(crate) fn ensure_data_dir() -> Result<()> {
+    let dir = data_dir()?;
+    if !dir.exists() {
+        create_dir_all(&dir)?;
+    }
+    Ok(())
+}
+
+/// Sidecar file the registry `flock` is taken on. Locking a separate file
+/// (rather than `projects.json` itself) keeps the lock valid across the
+/// atomic rename that replaces `projects.json`.
+fn get_lock_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join("projects.lock"))
+}
+
+/// Parses the contents of `projects.json`. The returned flag is `true` when
+/// the input was the legacy flat format and should be rewritten.
+fn parse_registry(
+    contents: &str,
+) -> Result<(HashMap<String, ProjectEntry>, bool), serde_json::Error> {
+    match serde_json::from_str::<RegistryOnDisk>(contents)? {
+        RegistryOnDisk::Versioned(file) => Ok((file.projects, false)),
+        RegistryOnDisk::Legacy(flat) => {
+            let projects = flat
+                .into_iter()
+                .map(|(name, path)| {
+                    (
+                        name,
+                        ProjectEntry {
+                            path,
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect();
+            Ok((projects, true))
+        }
+    }
+}
+
+fn serialize_registry(
+    projects: &HashMap<String, ProjectEntry>,
+) -> Result<String, serde_json::Error> {
+    let file = RegistryFile {
+        version: REGISTRY_VERSION,
+        projects: projects.clone(),
+    };
+    serde_json::to_string_pretty(&file)
+}
+
+/// Reads the registry without locking or writing. A missing file is an
+/// empty registry. When the file is in the legacy flat format, its original
+/// contents are returned alongside so the caller can back them up before the
+/// migrated form replaces them.
+fn load_registry() -> Result<(HashMap<String, ProjectEntry>, Option<String>)> {
+    ensure_data_dir()?;
+    let data_path = get_data_path()?;
     if !data_path.exists() {
-        write(
-            &data_path,
-            serde_json::to_string_pretty(&HashMap::<String, String>::new()).unwrap(),
-        )
-        .expect("Failed to create data json");
+        return Ok((HashMap::new(), None));
+    }
+
+    let contents = read_to_string(&data_path)?;
+    let (projects, migrated) = parse_registry(&contents).map_err(|source| Error::Parse {
+        path: data_path,
+        source,
+    })?;
+    Ok((projects, migrated.then_some(contents)))
+}
+
+pub fn get_projects() -> Result<HashMap<String, ProjectEntry>> {
+    let (projects, legacy) = load_registry()?;
+    if legacy.is_some() {
+        // Persist the migration right away. `update_projects` re-reads under
+        // the lock, so if another process migrated first this is a no-op
+        // rewrite rather than a clobber.
+        update_projects(|_| Ok(()))?;
+    }
+    Ok(projects)
+}
+
+/// Runs one read-modify-write cycle on the registry: takes the registry
+/// lock, reads the current map, applies `f`, and atomically writes the
+/// result. If `f` fails nothing is written. Every mutation goes through
+/// here so concurrent `vcode` processes can't lose each other's changes.
+pub fn update_projects<T>(
+    f: impl FnOnce(&mut HashMap<String, ProjectEntry>) -> Result<T>,
+) -> Result<T> {
+    ensure_data_dir()?;
+    let _lock = FileLock::acquire(&get_lock_path()?)?;
+    let (mut projects, legacy) = load_registry()?;
+    let out = f(&mut projects)?;
+
+    if let Some(contents) = legacy {
+        // Keep the original next to the new file so a downgrade (or a bug in
+        // the migration) never costs anyone their registry.
+        write_atomic(&get_data_path()?.with_extension("json.v1.bak"), contents.as_bytes())?;
+    }
+    store_projects(&projects)?;
+    Ok(out)
+}
+
+/// What to do when a batch add (`vcode scan`, `search --fs`) finds a name
+/// that is already registered to a different path.
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictStrategy {
+    /// Leave the registered project alone and don't add the new one.
+    #[default]
+    Skip,
+    /// Replace the registered project with the new one.
+    Overwrite,
+    /// Register the new one as `name-2` (or `-3`, ...).
+    Suffix,
+    /// Register the new one as `parent/name`, using its parent directory.
+    Parent,
+}
+
+/// How a project found on disk is named when no name is given (`vcode
+/// scan`, `vcode here`, `vcode add <path>`). Strategies that find nothing
+/// for a directory fall back to its name.
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum NameStrategy {
+    /// The directory's name.
+    #[default]
+    Dirname,
+    /// The package name from Cargo.toml, package.json, pyproject.toml or go.mod.
+    Manifest,
+    /// `owner/repo` from the `origin` remote.
+    GitRemote,
+    /// `parent/dir`, with the name of the directory it's in.
+    #[value(name = "parent/dir")]
+    #[serde(rename = "parent/dir")]
+    ParentDir,
+}
+
+/// What [`insert_project`] did with a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Insertion {
+    /// Registered under its own name.
+    Added,
+    /// Already registered under this name at this path; nothing changed.
+    Unchanged,
+    /// The name was taken and the strategy said to skip it.
+    Skipped,
+    /// Replaced a different project that had this name.
+    Overwrote,
+    /// The name was taken, so it was registered under this one instead.
+    Renamed(String),
+}
+
+/// Registers `name` at `path`. Re-registering the same path is a no-op. If
+/// the name belongs to a different path this fails with [`Error::Conflict`],
+/// unless `force` is set, in which case the old entry is replaced outright.
+pub fn set_project(name: &str, path: &str, force: bool) -> Result<()> {
+    let strategy = if force {
+        ConflictStrategy::Overwrite
+    } else {
+        ConflictStrategy::Skip
+    };
+    update_projects(|projects| set_in(projects, name, path, strategy))
+}
+
+/// In-memory half of [`set_project`].
+fn set_in(
+    projects: &mut HashMap<String, ProjectEntry>,
+    name: &str,
+    path: &str,
+    strategy: ConflictStrategy,
+) -> Result<()> {
+    match insert_project(projects, name, path, strategy) {
+        Insertion::Skipped => Err(Error::Conflict(name.to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// In-memory half of [`set_project`] for batch callers that already hold
+/// the registry inside [`update_projects`]: registers `name` at `path`,
+/// resolving a clash with a differently-pathed entry via `strategy`.
+pub fn insert_project(
+    projects: &mut HashMap<String, ProjectEntry>,
+    name: &str,
+    path: &str,
+    strategy: ConflictStrategy,
+) -> Insertion {
+    let Some(existing) = projects.get(name) else {
+        projects.insert(name.to_string(), ProjectEntry::new(path.to_string()));
+        return Insertion::Added;
+    };
+    if existing.path == path {
+        return Insertion::Unchanged;
     }
 
-    serde_json::from_str(&read_to_string(&data_path).unwrap())
-        .expect("Failed to parse projects.json")
+    match strategy {
+        ConflictStrategy::Skip => Insertion::Skipped,
+        ConflictStrategy::Overwrite => {
+            projects.insert(name.to_string(), ProjectEntry::new(path.to_string()));
+            Insertion::Overwrote
+        }
+        ConflictStrategy::Suffix => {
+            let free = free_suffixed_name(projects, name);
+            projects.insert(free.clone(), ProjectEntry::new(path.to_string()));
+            Insertion::Renamed(free)
+        }
+        ConflictStrategy::Parent => {
+            let parent = std::path::Path::new(path)
+                .parent()
+                .map(path_basename)
+                .filter(|p| !p.is_empty() && p != "/");
+            let candidate = match parent {
+                Some(parent) => format!("{}/{}", parent, name),
+                None => name.to_string(),
+            };
+            // The prefixed name can itself be taken (same parent name under
+            // two roots); fall back to numbering it rather than failing.
+            let free = match projects.get(&candidate) {
+                Some(e) if e.path == path => return Insertion::Unchanged,
+                Some(_) => free_suffixed_name(projects, &candidate),
+                None => candidate,
+            };
+            projects.insert(free.clone(), ProjectEntry::new(path.to_string()));
+            Insertion::Renamed(free)
+        }
+    }
 }
 
-pub fn set_project(name: &str, path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut projects = get_projects();
-    projects.insert(name.to_string(), path.to_string());
-    write_projects(&projects)
+/// First of `name-2`, `name-3`, ... that isn't registered.
+fn free_suffixed_name(projects: &HashMap<String, ProjectEntry>, name: &str) -> String {
+    (2..)
+        .map(|n| format!("{}-{}", name, n))
+        .find(|candidate| !projects.contains_key(candidate))
+        .expect("unbounded range always yields a free name")
 }
 
-/// Persist the entire project map in one write. Used by batch operations
-/// (prune, future imports) to avoid N rewrites of the same JSON file.
-pub fn write_projects(projects: &HashMap<String, String>) -> Result<(), Box<dyn std::error::Error>> {
-    let json = serde_json::to_string_pretty(projects)?;
-    write(get_data_path(), json)?;
+/// Persist the entire project map in one write, replacing whatever is on
+/// disk. Prefer [`update_projects`] for read-modify-write; this is for
+/// callers that genuinely want to overwrite (imports, resets).
+pub fn write_projects(projects: &HashMap<String, ProjectEntry>) -> Result<()> {
+    ensure_data_dir()?;
+    let _lock = FileLock::acquire(&get_lock_path()?)?;
+    store_projects(projects)
+}
+
+/// Serializes and atomically writes the registry. Caller must hold the lock.
+fn store_projects(projects: &HashMap<String, ProjectEntry>) -> Result<()> {
+    let json = serialize_registry(projects)?;
+    write_atomic(&get_data_path()?, json.as_bytes())?;
     Ok(())
 }
 
-/// Resolves `raw_path`, verifies it's an existing directory, and inserts/updates
-/// the project entry. Returns the resolved canonical path on success, or a
-/// human-readable error explaining what went wrong.
+/// Records a successful open of `name` in its usage stats (open count and
+/// last-opened time), which feed frecency ranking and `vcode recent`.
+/// Unknown names are ignored so path-fallback opens don't need a guard.
+pub fn record_open(name: &str) -> Result<()> {
+    update_projects(|projects| {
+        if let Some(entry) = projects.get_mut(name) {
+            entry.open_count += 1;
+            entry.last_opened = Some(now_secs());
+        }
+        Ok(())
+    })
+}
+
+/// Resolves `raw_path`, verifies it's an existing directory, and registers
+/// it under `name` with [`set_project`]'s conflict rules. Returns the
+/// resolved canonical path on success.
 ///
 /// Centralizes the resolve+validate+write pattern shared by `add`, `update`,
 /// and other future write operations so they don't drift in their error
 /// messages or validation rules.
-pub fn set_project_validated(name: &str, raw_path: &str) -> Result<PathBuf, String> {
-    let resolved = resolve_path(raw_path);
+pub fn set_project_validated(name: &str, raw_path: &str, force: bool) -> Result<PathBuf> {
+    let resolved = validate_project_dir(raw_path)?;
+    set_project(name, path_str(&resolved)?, force)?;
+    Ok(resolved)
+}
+
+/// Points an existing project at a new directory, keeping its usage stats,
+/// tags and notes. Fails with [`Error::NotFound`] for unknown names.
+pub fn relocate_project(name: &str, raw_path: &str) -> Result<PathBuf> {
+    let resolved = validate_project_dir(raw_path)?;
+    let path = path_str(&resolved)?.to_string();
+    update_projects(|projects| match projects.get_mut(name) {
+        Some(entry) => {
+            entry.move_to(path);
+            Ok(())
+        }
+        None => Err(Error::NotFound(name.to_string())),
+    })?;
+    Ok(resolved)
+}
+
+/// Trims and lowercases user-supplied tags so `Client-A` and `client-a`
+/// are one group. Tags are single shell words: whitespace and commas are
+/// rejected rather than silently split.
+pub fn normalize_tags(raw: &[String]) -> Result<Vec<String>> {
+    let mut tags = Vec::with_capacity(raw.len());
+    for tag in raw {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || tag.contains(|c: char| c.is_whitespace() || c == ',') {
+            return Err(Error::InvalidTag(tag));
+        }
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    Ok(tags)
+}
+
+/// Adds `tags` to a project. Returns the tags that were newly added.
+pub fn tag_project(name: &str, tags: &[String]) -> Result<Vec<String>> {
+    let tags = normalize_tags(tags)?;
+    update_projects(|projects| match projects.get_mut(name) {
+        Some(entry) => Ok(entry.add_tags(&tags)),
+        None => Err(Error::NotFound(name.to_string())),
+    })
+}
+
+/// Removes `tags` from a project. Returns the tags that were actually removed.
+pub fn untag_project(name: &str, tags: &[String]) -> Result<Vec<String>> {
+    let tags = normalize_tags(tags)?;
+    update_projects(|projects| match projects.get_mut(name) {
+        Some(entry) => Ok(entry.remove_tags(&tags)),
+        None => Err(Error::NotFound(name.to_string())),
+    })
+}
+
+/// Every tag in use with the number of projects carrying it, sorted by tag.
+pub fn tag_counts(projects: &HashMap<String, ProjectEntry>) -> Vec<(String, usize)> {
+    let mut counts: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+    for entry in projects.values() {
+        for tag in &entry.tags {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+    counts.into_iter().map(|(t, n)| (t.to_string(), n)).collect()
+}
+
+/// Resolves `raw_path` and checks that it's an existing directory.
+pub fn validate_project_dir(raw_path: &str) -> Result<PathBuf> {
+    let resolved = resolve_path(raw_path)?;
     if !resolved.exists() {
-        return Err(format!("Path does not exist: {}", resolved.display()));
+        return Err(Error::InvalidPath {
+            path: resolved,
+            reason: "does not exist",
+        });
     }
     if !resolved.is_dir() {
-        return Err(format!("Path is not a directory: {}", resolved.display()));
+        return Err(Error::InvalidPath {
+            path: resolved,
+            reason: "is not a directory",
+        });
     }
-    let path_str = resolved
-        .to_str()
-        .ok_or_else(|| format!("Path contains invalid UTF-8: {}", resolved.display()))?;
-    set_project(name, path_str).map_err(|e| format!("Failed to write registry: {}", e))?;
     Ok(resolved)
 }
 
+/// Borrows `path` as UTF-8, which the JSON registry requires.
+pub fn path_str(path: &std::path::Path) -> Result<&str> {
+    path.to_str().ok_or_else(|| Error::InvalidPath {
+        path: path.to_path_buf(),
+        reason: "contains invalid UTF-8",
+    })
+}
+
 /// Extracts the final path segment as an owned String, or the lossy full path
 /// if no basename can be determined (e.g. root `/`).
 pub fn path_basename(path: &std::path::Path) -> String {
@@ -78,40 +530,50 @@ pub fn path_basename(path: &std::path::Path) -> String {
         .unwrap_or_else(|| path.to_string_lossy().into_owned())
 }
 
-pub fn delete_project(name: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut projects = get_projects();
-    projects.remove(name);
-    write_projects(&projects)
+pub fn delete_project(name: &str) -> Result<()> {
+    update_projects(|projects| match projects.remove(name) {
+        Some(_) => Ok(()),
+        None => Err(Error::NotFound(name.to_string())),
+    })
 }
 
-pub fn rename_project(old_name: &str, new_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut projects = get_projects();
-
-    if let Some(path) = projects.remove(old_name) {
-        projects.insert(new_name.to_string(), path);
-        write_projects(&projects)
-    } else {
-        Err(format!("Project '{}' not found", old_name).into())
-    }
+/// Renames a project, keeping its metadata. Fails with [`Error::Conflict`]
+/// if `new_name` is already registered, unless `force` is set, in which
+/// case that other entry is dropped.
+pub fn rename_project(old_name: &str, new_name: &str, force: bool) -> Result<()> {
+    update_projects(|projects| {
+        if !projects.contains_key(old_name) {
+            return Err(Error::NotFound(old_name.to_string()));
+        }
+        if old_name == new_name {
+            return Ok(());
+        }
+        if projects.contains_key(new_name) && !force {
+            return Err(Error::Conflict(new_name.to_string()));
+        }
+        if let Some(entry) = projects.remove(old_name) {
+            projects.insert(new_name.to_string(), entry);
+        }
+        Ok(())
+    })
 }
 
-pub fn reset_projects() -> Result<(), Box<dyn std::error::Error>> {
-    write(get_data_path(), "{}")?;
-    Ok(())
+pub fn reset_projects() -> Result<()> {
+    write_projects(&HashMap::new())
 }
 
-pub fn resolve_path(input: &str) -> PathBuf {
+/// Expands `~`, then canonicalizes `input` if it exists, or makes it absolute
+/// against the current directory if it doesn't.
+pub fn resolve_path(input: &str) -> Result<PathBuf> {
     let expanded = expand_tilde(input);
     let path = PathBuf::from(&expanded);
 
     if path.exists() {
-        std::fs::canonicalize(&path).expect("Failed to canonicalize path")
+        Ok(std::fs::canonicalize(&path)?)
     } else if path.is_absolute() {
-        path
+        Ok(path)
     } else {
-        std::env::current_dir()
-            .expect("Failed to get current dir")
-            .join(path)
+        Ok(std::env::current_dir()?.join(path))
     }
 }
 
@@ -133,7 +595,7 @@ fn expand_tilde(input: &str) -> String {
 /// Used by commands that want to fall back to opening a path when a project
 /// name lookup fails (e.g. `vcode .`, `vcode ../foo`, `vcode ~/work/x`).
 pub fn try_resolve_existing_dir(input: &str) -> Option<PathBuf> {
-    let resolved = resolve_path(input);
+    let resolved = resolve_path(input).ok()?;
     if resolved.is_dir() { Some(resolved) } else { None }
 }
 
@@ -145,7 +607,7 @@ mod tests {
     #[test]
     fn test_resolve_path_current_dir() {
         let current = env::current_dir().unwrap();
-        let resolved = resolve_path(".");
+        let resolved = resolve_path(".").unwrap();
         assert_eq!(resolved, current);
     }
 
@@ -153,13 +615,13 @@ mod tests {
     fn test_resolve_path_relative() {
         let current = env::current_dir().unwrap();
         let expected = current.join("test_path");
-        let resolved = resolve_path("test_path");
+        let resolved = resolve_path("test_path").unwrap();
         assert_eq!(resolved, expected);
     }
 
     #[test]
     fn test_get_data_path() {
-        let path = get_data_path();
+        let path = get_data_path().unwrap();
         assert!(path.to_string_lossy().contains("vcode"));
         assert!(path.to_string_lossy().ends_with("projects.json"));
     }
@@ -194,4 +656,131 @@ mod tests {
     fn test_try_resolve_existing_dir_missing() {
         assert!(try_resolve_existing_dir("/no/such/path/should/exist/here").is_none());
     }
+
+    #[test]
+    fn test_parse_registry_migrates_legacy() {
+        let (projects, migrated) =
+            parse_registry(r#"{"api": "/work/api", "web": "/work/web"}"#).unwrap();
+        assert!(migrated);
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects["api"].path, "/work/api");
+        assert_eq!(projects["api"].open_count, 0);
+        assert!(projects["api"].added_at.is_none());
+    }
+
+    #[test]
+    fn test_parse_registry_legacy_project_named_version() {
+        let (projects, migrated) = parse_registry(r#"{"version": "/work/version"}"#).unwrap();
+        assert!(migrated);
+        assert_eq!(projects["version"].path, "/work/version");
+    }
+
+    #[test]
+    fn test_parse_registry_empty_legacy() {
+        let (projects, migrated) = parse_registry("{}").unwrap();
+        assert!(migrated);
+        assert!(projects.is_empty());
+    }
+
+    #[test]
+    fn test_frecency_prefers_recent_and_frequent() {
+        let now = 10_000_000;
+        let never = ProjectEntry::default();
+        let recent = ProjectEntry {
+            open_count: 2,
+            last_opened: Some(now - 60),
+            ..Default::default()
+        };
+        let stale_but_busy = ProjectEntry {
+            open_count: 40,
+            last_opened: Some(now - 30 * 24 * 60 * 60),
+            ..Default::default()
+        };
+        let stale_and_rare = ProjectEntry {
+            open_count: 2,
+            last_opened: Some(now - 30 * 24 * 60 * 60),
+            ..Default::default()
+        };
+
+        assert_eq!(never.frecency(now), 0.0);
+        assert!(recent.frecency(now) > stale_and_rare.frecency(now));
+        assert!(stale_but_busy.frecency(now) > recent.frecency(now));
+    }
+
+    fn registry(pairs: &[(&str, &str)]) -> HashMap<String, ProjectEntry> {
+        pairs
+            .iter()
+            .map(|(n, p)| (n.to_string(), ProjectEntry::new(p.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_project_same_path_is_unchanged() {
+        let mut projects = registry(&[("api", "/a/api")]);
+        let out = insert_project(&mut projects, "api", "/a/api", ConflictStrategy::Skip);
+        assert_eq!(out, Insertion::Unchanged);
+    }
+
+    #[test]
+    fn test_insert_project_strategies() {
+        let mut projects = registry(&[("api", "/a/api")]);
+
+        let out = insert_project(&mut projects, "api", "/b/api", ConflictStrategy::Skip);
+        assert_eq!(out, Insertion::Skipped);
+        assert_eq!(projects["api"].path, "/a/api");
+
+        let out = insert_project(&mut projects, "api", "/b/api", ConflictStrategy::Suffix);
+        assert_eq!(out, Insertion::Renamed("api-2".to_string()));
+        let out = insert_project(&mut projects, "api", "/c/api", ConflictStrategy::Suffix);
+        assert_eq!(out, Insertion::Renamed("api-3".to_string()));
+
+        let out = insert_project(&mut projects, "api", "/work/d/api", ConflictStrategy::Parent);
+        assert_eq!(out, Insertion::Renamed("d/api".to_string()));
+        let out = insert_project(&mut projects, "api", "/other/d/api", ConflictStrategy::Parent);
+        assert_eq!(out, Insertion::Renamed("d/api-2".to_string()));
+
+        let out = insert_project(&mut projects, "api", "/e/api", ConflictStrategy::Overwrite);
+        assert_eq!(out, Insertion::Overwrote);
+        assert_eq!(projects["api"].path, "/e/api");
+    }
+
+    #[test]
+    fn test_set_project_conflicts_with_a_path_registered_elsewhere() {
+        let mut projects = registry(&[("api", "/a"), ("web", "/b")]);
+        let out = set_in(&mut projects, "api", "/b", ConflictStrategy::Skip);
+        assert!(matches!(out, Err(Error::Conflict(name)) if name == "api"));
+        assert_eq!((projects["api"].path.as_str(), projects["web"].path.as_str()), ("/a", "/b"));
+
+        assert!(set_in(&mut projects, "api", "/a", ConflictStrategy::Skip).is_ok());
+    }
+
+    #[test]
+    fn test_tags_normalize_and_dedup() {
+        let raw = vec![" Client-A ".to_string(), "client-a".to_string(), "oss".to_string()];
+        let tags = normalize_tags(&raw).unwrap();
+        assert_eq!(tags, vec!["client-a", "oss"]);
+        assert!(normalize_tags(&["two words".to_string()]).is_err());
+        assert!(normalize_tags(&["".to_string()]).is_err());
+
+        let mut entry = ProjectEntry::new("/work/api".to_string());
+        assert_eq!(entry.add_tags(&tags), tags);
+        assert!(entry.add_tags(&tags).is_empty());
+        assert!(entry.has_all_tags(&["oss".to_string()]));
+        assert_eq!(entry.remove_tags(&["oss".to_string(), "x".to_string()]), vec!["oss"]);
+        assert_eq!(entry.tags, vec!["client-a"]);
+    }
+
+    #[test]
+    fn test_registry_round_trip() {
+        let mut projects = HashMap::new();
+        let mut entry = ProjectEntry::new("/work/api".to_string());
+        entry.tags = vec!["client-a".to_string()];
+        entry.open_count = 3;
+        projects.insert("api".to_string(), entry.clone());
+
+        let json = serialize_registry(&projects).unwrap();
+        let (parsed, migrated) = parse_registry(&json).unwrap();
+        assert!(!migrated);
+        assert_eq!(parsed["api"], entry);
+    }
 }
@@ -0,0 +1,142 @@
+//! Crash- and race-safe file persistence
+//!
+//! Everything vcode persists (`projects.json`, `config.json`) goes through
+//! [`write_atomic`], so a crash mid-write leaves either the old or the new
+//! file, never a truncated one. Read-modify-write cycles additionally hold a
+//! [`FileLock`] so two concurrent `vcode` processes (a shell hook plus a
+//! manual `vcode add`) can't interleave and drop each other's changes.
+
+use std::fs::{File, OpenOptions, rename};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Writes `contents` to a temp file next to `path`, fsyncs it, then renames it
+/// over `path`. The rename is atomic on POSIX filesystems as long as both
+/// live in the same directory, which is why the temp file isn't in `/tmp`.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = temp_path_for(path);
+
+    let result = (|| {
+        let mut file = File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        rename(&tmp, path)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// `dir/.name.tmp-<pid>`: hidden, and unique per process so two writers
+/// never share a temp file even if one of them doesn't hold the lock.
+fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.tmp-{}", name, std::process::id()))
+}
+
+/// An exclusive advisory `flock` on a sidecar lock file, released on drop.
+///
+/// The lock is per open file description, so acquiring it twice in one
+/// process blocks forever: take it once at the outermost read-modify-write
+/// and call only lock-free helpers underneath.
+pub struct FileLock {
+    // Held only to keep the descriptor (and with it the lock) alive.
+    _file: File,
+}
+
+impl FileLock {
+    /// Blocks until the lock on `path` is acquired, creating the lock file
+    /// if needed.
+    pub fn acquire(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+        lock_exclusive(&file)?;
+        Ok(Self { _file: file })
+    }
+}
+
+#[cfg(unix)]
+fn lock_exclusive(file: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+    loop {
+        // SAFETY: the fd is owned by `file`, which outlives this call.
+        // Closing it (when `FileLock` drops) releases the lock.
+        let rc = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) };
+        if rc == 0 {
+            return Ok(());
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn lock_exclusive(_file: &File) -> io::Result<()> {
+    // No advisory locking off unix; atomic renames still prevent torn files.
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_atomic_replaces_contents() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("projects.json");
+        fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, b"new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        let leftovers: Vec<_> = fs::read_dir(dir.path()).unwrap().flatten().collect();
+        assert_eq!(leftovers.len(), 1, "temp file should be renamed away");
+    }
+
+    #[test]
+    fn test_write_atomic_missing_dir_fails_cleanly() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nope").join("projects.json");
+        assert!(write_atomic(&path, b"x").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_lock_serializes_threads() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let dir = TempDir::new().unwrap();
+        let lock_path = Arc::new(dir.path().join("x.lock"));
+        let inside = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let lock_path = Arc::clone(&lock_path);
+                let inside = Arc::clone(&inside);
+                std::thread::spawn(move || {
+                    let _guard = FileLock::acquire(&lock_path).unwrap();
+                    assert_eq!(inside.fetch_add(1, Ordering::SeqCst), 0);
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                    inside.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+
+        for h in handles {
+            h.join().unwrap();
+        }
+    }
+}
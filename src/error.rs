@@ -0,0 +1,120 @@
+//! Library error type
+//!
+//! Every public `core` and `scanner` API returns [`Result`], so embedding
+//! vcode never panics or prompts on bad state. Deciding what to do about an
+//! error (print it, exit, run first-time setup) is the CLI's job in
+//! `commands.rs`.
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A filesystem or process operation failed.
+    Io(io::Error),
+    /// A JSON file vcode owns couldn't be parsed.
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    /// In-memory state couldn't be serialized to JSON.
+    Serialize(serde_json::Error),
+    /// No project is registered under this name.
+    NotFound(String),
+    /// A project is already registered under this name.
+    Conflict(String),
+    /// A tag is empty or contains whitespace or commas.
+    InvalidTag(String),
+    /// An editor's command template renders to nothing to run.
+    InvalidTemplate(String),
+    /// A path argument doesn't point where it needs to.
+    InvalidPath { path: PathBuf, reason: &'static str },
+    /// `config.json` doesn't exist yet; the caller should run first-time setup.
+    ConfigMissing(PathBuf),
+    /// The platform doesn't provide a standard directory (`"data"`, `"config"`, `"home"`).
+    NoDir(&'static str),
+    /// The user cancelled an interactive prompt.
+    Cancelled,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse { path, source } => {
+                write!(f, "Failed to parse {}: {}", path.display(), source)
+            }
+            Error::Serialize(e) => write!(f, "Failed to serialize: {}", e),
+            Error::NotFound(name) => write!(f, "Project '{}' not found", name),
+            Error::Conflict(name) => write!(f, "Project '{}' already exists", name),
+            Error::InvalidTag(tag) => write!(
+                f,
+                "Invalid tag '{}': tags can't be empty or contain spaces or commas",
+                tag
+            ),
+            Error::InvalidTemplate(editor) => {
+                write!(f, "Editor '{}' has an empty command template", editor)
+            }
+            Error::InvalidPath { path, reason } => {
+                write!(f, "Path {}: {}", reason, path.display())
+            }
+            Error::ConfigMissing(path) => {
+                write!(f, "No configuration found at {}", path.display())
+            }
+            Error::NoDir(kind) => write!(f, "Could not determine {} directory", kind),
+            Error::Cancelled => f.write_str("Cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Parse { source, .. } => Some(source),
+            Error::Serialize(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Serialize(e)
+    }
+}
+
+impl From<dialoguer::Error> for Error {
+    fn from(e: dialoguer::Error) -> Self {
+        match e {
+            dialoguer::Error::IO(io) => Error::Io(io),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_match_cli_wording() {
+        let err = Error::InvalidPath {
+            path: PathBuf::from("/no/such"),
+            reason: "does not exist",
+        };
+        assert_eq!(err.to_string(), "Path does not exist: /no/such");
+        assert_eq!(
+            Error::NotFound("api".to_string()).to_string(),
+            "Project 'api' not found"
+        );
+    }
+}
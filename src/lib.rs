@@ -5,22 +5,28 @@
 //!
 //! - `core`: Core business logic (config, projects, editor integration)
 //! - `scanner`: Project scanning and detection
+//! - `shell`: `vcode init` shell functions and completion
 //! - `ui`: User interface components (logging, table display)
 //! - `commands`: Command handlers for CLI operations
+//! - `error`: The [`Error`] type returned by all `core` and `scanner` APIs
 
 pub mod commands;
 pub mod core;
+pub mod error;
 pub mod scanner;
+pub mod shell;
 pub mod ui;
 
 // Re-export commonly used items for convenience
+pub use error::{Error, Result};
 pub use core::{
-    Config, delete_project, get_config, get_projects, init_config, open_with_editor,
+    ConflictStrategy, Config, FileTarget, NameStrategy, OpenRequest, ProjectEntry, delete_project, get_config, get_projects, open_with_editor,
     path_basename, rename_project, reset_projects, resolve_path, set_project,
     set_project_validated, try_resolve_existing_dir, write_projects,
 };
 pub use scanner::{
-    FilterMode, FoundProject, ProjectType, add_projects, detect_project_type,
+    Detection, FilterMode, FoundProject, Framework, ProjectType, add_projects, detect_project,
+    detect_project_type,
     interactive_select_projects, is_project_directory, scan_projects,
 };
 pub use ui::{LogType, log, print_table};
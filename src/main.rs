@@ -1,6 +1,7 @@
 use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::Shell;
-use vcode::{APP_NAME, LogType, commands, commands::ConfigAction, commands::SortKey, log};
+use vcode::shell::{DEFAULT_JUMP_COMMAND, InitShell};
+use vcode::{APP_NAME, LogType, NameStrategy, commands, commands::ConfigAction, commands::IndexAction, commands::ListView, commands::RegisterOptions, commands::SyncMode, commands::WalkOptions, log};
 
 /// A fast CLI project launcher for your favorite code editor
 #[derive(Parser)]
@@ -14,6 +15,10 @@ struct Cli {
     /// Project name to open
     project_name: Option<String>,
 
+    /// File inside the project to focus, optionally with a position
+    /// (`src/main.rs:42` or `src/main.rs:42:7`)
+    file: Option<String>,
+
     /// Reuse existing editor window
     #[arg(short, long)]
     reuse: bool,
@@ -43,6 +48,12 @@ enum Commands {
         /// Search filesystem for directory with this name and add it
         #[arg(long, short = 'f')]
         find: bool,
+        /// Replace an existing project registered under the same name
+        #[arg(long)]
+        force: bool,
+        /// How to name the project when only a path is given (defaults to config `naming`)
+        #[arg(long, value_enum)]
+        naming: Option<NameStrategy>,
     },
 
     /// Remove a project
@@ -61,12 +72,17 @@ enum Commands {
         /// Interactive mode - select a project to open
         #[arg(short, long)]
         interactive: bool,
-        /// Sort projects by this key
-        #[arg(short, long, value_enum, default_value_t = SortKey::Name)]
-        sort: SortKey,
+        #[command(flatten)]
+        view: ListView,
         /// Filter by project type (e.g. rust, javascript, python, go)
         #[arg(short = 'F', long)]
         filter: Option<String>,
+        /// Only show projects with this tag (repeat to require several)
+        #[arg(short = 't', long = "tag")]
+        tag: Vec<String>,
+        /// Show every tag with its project count instead of projects
+        #[arg(long, conflicts_with_all = ["interactive", "tag"])]
+        tags: bool,
     },
 
     /// Search projects by name or path
@@ -74,12 +90,19 @@ enum Commands {
     Search {
         /// Search query
         query: String,
-        /// Search the actual filesystem (under $HOME) for directories whose
-        /// name contains the query, then add the selected ones as projects.
-        /// Use this when you know a project exists on disk but `vcode scan`
-        /// didn't pick it up (e.g. it's outside your projects root).
+        /// Search the actual filesystem (every project root) for directories
+        /// whose name contains the query, then add the selected ones as
+        /// projects. Use this when you know a project exists on disk but
+        /// `vcode scan` didn't pick it up (e.g. it's nested deeper than the
+        /// scan depth).
         #[arg(short = 'f', long)]
         fs: bool,
+        /// Only search projects with this tag (repeat to require several)
+        #[arg(short = 't', long = "tag", conflicts_with = "fs")]
+        tag: Vec<String>,
+        /// With --fs, also enter directories excluded by ignore files
+        #[arg(long, requires = "fs")]
+        no_ignore: bool,
     },
 
     /// Rename a project
@@ -89,21 +112,51 @@ enum Commands {
         old_name: String,
         /// New project name
         new_name: String,
+        /// Replace an existing project registered under the new name
+        #[arg(long)]
+        force: bool,
     },
 
     /// Scan directory for projects
     Scan {
-        /// Directory to scan (defaults to configured projects_root)
+        /// Directory to scan (defaults to every configured project root)
         path: Option<String>,
-        /// Depth to scan (default: 1)
-        #[arg(short, long, default_value = "1")]
-        depth: u32,
+        /// Depth to scan (default: the root's configured depth, or 1)
+        #[arg(short, long)]
+        depth: Option<u32>,
         /// Filter mode: auto (detect projects) or all (include all directories)
         #[arg(short, long, default_value = "auto")]
         filter: String,
         /// Skip interactive review and add all found projects
         #[arg(long)]
         no_review: bool,
+        #[command(flatten)]
+        register: RegisterOptions,
+        #[command(flatten)]
+        walk: WalkOptions,
+        /// Review the projects `vcode watch --review` queued instead of scanning
+        #[arg(long, conflicts_with_all = ["path", "depth", "filter", "naming"])]
+        pending: bool,
+        /// Reconcile the registry with what's on disk: add new projects,
+        /// follow moved ones and remove vanished ones
+        #[arg(long, conflicts_with = "pending")]
+        sync: bool,
+        /// With --sync, print the planned changes as JSON and change nothing
+        #[arg(long, requires = "sync")]
+        dry_run: bool,
+    },
+
+    /// Watch the project roots and register new projects as they appear (Linux)
+    Watch {
+        /// Levels below each root where projects live (default: the root's configured depth)
+        #[arg(short, long)]
+        depth: Option<u32>,
+        /// Queue new projects for `vcode scan --pending` instead of registering them
+        #[arg(long)]
+        review: bool,
+        /// Tag every registered project (repeat for several tags)
+        #[arg(short = 't', long = "tag")]
+        tag: Vec<String>,
     },
 
     /// Manage configuration
@@ -122,14 +175,57 @@ enum Commands {
 
     /// Register the current directory as a project and open it
     Here {
-        /// Project name (defaults to current directory basename)
+        /// Project name (defaults to one picked by the naming strategy)
         name: Option<String>,
+        /// Replace an existing project registered under the same name
+        #[arg(long)]
+        force: bool,
+        /// How to name the project when no name is given (defaults to config `naming`)
+        #[arg(long, value_enum)]
+        naming: Option<NameStrategy>,
     },
 
     /// Print the path of a project (for shell scripting, e.g. `cd $(vcode where api)`)
     Where {
         /// Project name (supports fuzzy match)
         name: String,
+        /// Only consider projects with this tag (repeat to require several)
+        #[arg(short = 't', long = "tag")]
+        tag: Vec<String>,
+    },
+
+    /// Add tags to a project
+    Tag {
+        /// Project name
+        name: String,
+        /// Tags to add
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+
+    /// Remove tags from a project
+    Untag {
+        /// Project name
+        name: String,
+        /// Tags to remove
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+
+    /// Show the most recently opened projects
+    Recent {
+        /// Number of projects to show
+        #[arg(short = 'n', long, default_value = "10")]
+        limit: usize,
+        /// Interactive mode - select a recent project to reopen
+        #[arg(short, long)]
+        interactive: bool,
+    },
+
+    /// Manage the directory index that speeds up filesystem search
+    Index {
+        #[command(subcommand)]
+        action: IndexAction,
     },
 
     /// Remove projects whose paths no longer exist on disk
@@ -139,6 +235,23 @@ enum Commands {
         yes: bool,
     },
 
+    /// Find where stale projects were moved and update their paths
+    Repair {
+        /// Apply unambiguous matches and prune the rest without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Show git repositories with uncommitted changes or unpushed/unpulled commits
+    Status {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+        /// Only check projects with this tag (repeat to require several)
+        #[arg(short = 't', long = "tag")]
+        tag: Vec<String>,
+    },
+
     /// Change the path of an existing project
     Update {
         /// Project name
@@ -152,6 +265,26 @@ enum Commands {
         /// Target shell
         shell: Shell,
     },
+
+    /// Print shell functions for `cd`-ing into projects (`eval "$(vcode init zsh)"`)
+    Init {
+        /// Target shell
+        #[arg(value_enum)]
+        shell: InitShell,
+        /// Name of the jump command
+        #[arg(long, default_value = DEFAULT_JUMP_COMMAND)]
+        cmd: String,
+    },
+
+    /// Print completion candidates (used by the completion and `vcode init` scripts)
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// What to complete: projects, editors, tags, types, or line
+        kind: String,
+        /// The word being completed, or for `line` the words typed so far
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
 }
 
 
@@ -171,36 +304,97 @@ fn main() {
 
     match cli.command {
         Some(cmd) => match cmd {
-            Commands::Add { name, path, find } => commands::handle_add(name, path, find),
+            Commands::Add {
+                name,
+                path,
+                find,
+                force,
+                naming,
+            } => commands::handle_add(name, path, find, force, naming),
             Commands::Remove { name } => commands::handle_remove(name),
             Commands::List {
                 json,
                 interactive,
-                sort,
+                view,
                 filter,
-            } => commands::handle_list(json, interactive, cli.reuse, cli.editor, sort, filter),
-            Commands::Search { query, fs } => commands::handle_search(query, fs, cli.cd),
-            Commands::Rename { old_name, new_name } => commands::handle_rename(old_name, new_name),
+                tag,
+                tags,
+            } => {
+                if tags {
+                    commands::handle_list_tags(json)
+                } else {
+                    commands::handle_list(json, interactive, cli.reuse, cli.editor, view, filter, tag)
+                }
+            }
+            Commands::Search {
+                query,
+                fs,
+                tag,
+                no_ignore,
+            } => commands::handle_search(query, fs, cli.cd, tag, no_ignore),
+            Commands::Rename {
+                old_name,
+                new_name,
+                force,
+            } => commands::handle_rename(old_name, new_name, force),
             Commands::Scan {
                 path,
                 depth,
                 filter,
                 no_review,
-            } => commands::handle_scan(path, depth, filter, no_review),
+                register,
+                walk,
+                pending,
+                sync,
+                dry_run,
+            } => {
+                if pending {
+                    commands::handle_scan_pending(no_review, register.on_conflict, register.tag)
+                } else if sync {
+                    let mode = if dry_run {
+                        SyncMode::DryRun
+                    } else if no_review {
+                        SyncMode::ApplyAll
+                    } else {
+                        SyncMode::Review
+                    };
+                    commands::handle_scan_sync(path, depth, filter, mode, register, walk)
+                } else {
+                    commands::handle_scan(path, depth, filter, no_review, register, walk)
+                }
+            }
+            Commands::Watch { depth, review, tag } => commands::handle_watch(depth, review, tag),
             Commands::Config { action } => commands::handle_config(action),
             Commands::Clear { yes } => commands::handle_clear(yes),
-            Commands::Here { name } => commands::handle_here(name, cli.reuse, cli.editor),
-            Commands::Where { name } => commands::handle_where(name, cli.cd),
+            Commands::Here { name, force, naming } => {
+                commands::handle_here(name, force, naming, cli.reuse, cli.editor)
+            }
+            Commands::Where { name, tag } => commands::handle_where(name, cli.cd, tag),
+            Commands::Tag { name, tags } => commands::handle_tag(name, tags),
+            Commands::Untag { name, tags } => commands::handle_untag(name, tags),
+            Commands::Recent { limit, interactive } => {
+                commands::handle_recent(limit, interactive, cli.reuse, cli.editor)
+            }
+            Commands::Index { action } => commands::handle_index(action),
             Commands::Prune { yes } => commands::handle_prune(yes),
+            Commands::Repair { yes } => commands::handle_repair(yes),
+            Commands::Status { json, tag } => commands::handle_status(json, tag),
             Commands::Update { name, path } => commands::handle_update(name, path),
+            Commands::Init { shell, cmd } => commands::handle_init(shell, cmd),
+            Commands::Complete { kind, args } => {
+                commands::handle_complete(&Cli::command(), kind, args)
+            }
             Commands::Completions { shell } => {
                 let mut cmd = Cli::command();
-                clap_complete::generate(shell, &mut cmd, APP_NAME, &mut std::io::stdout());
+                let mut script = Vec::new();
+                clap_complete::generate(shell, &mut cmd, APP_NAME, &mut script);
+                let script = String::from_utf8_lossy(&script).into_owned();
+                print!("{}", vcode::shell::complete::dynamic_script(shell, script));
             }
         },
         None => match cli.project_name {
             Some(project_name) => {
-                commands::handle_open_project(project_name, cli.reuse, cli.editor, cli.cd)
+                commands::handle_open_project(project_name, cli.file, cli.reuse, cli.editor, cli.cd)
             }
             None => {
                 log("vcode - Quick Project Launcher", LogType::Info);
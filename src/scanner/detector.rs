@@ -1,3 +1,4 @@
+use super::rules::{ScanRules, scan_rules};
 use std::path::Path;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -17,6 +18,21 @@ pub enum ProjectType {
 }
 
 impl ProjectType {
+    /// Every detectable type, in display order (`Unknown` excluded).
+    pub const ALL: &'static [ProjectType] = &[
+        ProjectType::Rust,
+        ProjectType::JavaScript,
+        ProjectType::TypeScript,
+        ProjectType::Python,
+        ProjectType::Go,
+        ProjectType::Java,
+        ProjectType::CSharp,
+        ProjectType::Cpp,
+        ProjectType::Ruby,
+        ProjectType::Php,
+        ProjectType::Git,
+    ];
+
     pub fn name(&self) -> &'static str {
         match self {
             ProjectType::Rust => "Rust",
@@ -33,42 +49,305 @@ impl ProjectType {
             ProjectType::Unknown => "Unknown",
         }
     }
+
+    /// The type called `name` (case-insensitive), e.g. from a config rule.
+    pub fn from_name(name: &str) -> Option<ProjectType> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|t| t.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// A framework or app toolkit, recognised from manifest contents rather than
+/// marker files alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framework {
+    NextJs,
+    Vite,
+    Django,
+    FastApi,
+    Rails,
+    Spring,
+    Axum,
+    Tauri,
+}
+
+impl Framework {
+    pub const ALL: &'static [Framework] = &[
+        Framework::NextJs,
+        Framework::Vite,
+        Framework::Django,
+        Framework::FastApi,
+        Framework::Rails,
+        Framework::Spring,
+        Framework::Axum,
+        Framework::Tauri,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Framework::NextJs => "Next.js",
+            Framework::Vite => "Vite",
+            Framework::Django => "Django",
+            Framework::FastApi => "FastAPI",
+            Framework::Rails => "Rails",
+            Framework::Spring => "Spring",
+            Framework::Axum => "Axum",
+            Framework::Tauri => "Tauri",
+        }
+    }
+}
+
+/// Everything detected in one directory: languages ranked most likely first,
+/// types from the config's marker rules, and the frameworks found in their
+/// manifests.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Detection {
+    pub types: Vec<ProjectType>,
+    pub custom: Vec<String>,
+    pub frameworks: Vec<Framework>,
+}
+
+impl Detection {
+    /// The highest-ranked type.
+    pub fn primary(&self) -> Option<ProjectType> {
+        self.types.first().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty() && self.custom.is_empty()
+    }
+
+    /// `TypeScript, Rust · Tauri, Vite`, or `Unknown`.
+    pub fn label(&self) -> String {
+        if self.is_empty() {
+            return ProjectType::Unknown.name().to_string();
+        }
+        let types: Vec<&str> = self
+            .types
+            .iter()
+            .map(|t| t.name())
+            .chain(self.custom.iter().map(String::as_str))
+            .collect();
+        let mut label = types.join(", ");
+        if !self.frameworks.is_empty() {
+            let frameworks: Vec<&str> = self.frameworks.iter().map(|f| f.name()).collect();
+            label.push_str(" · ");
+            label.push_str(&frameworks.join(", "));
+        }
+        label
+    }
+
+    /// Whether `query` names any detected type or framework (case-insensitive,
+    /// so `--filter rust` and `--filter next.js` both work).
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.types.iter().any(|t| t.name().to_lowercase() == query)
+            || self.custom.iter().any(|c| c.to_lowercase() == query)
+            || self.frameworks.iter().any(|f| f.name().to_lowercase() == query)
+    }
 }
 
-const PROJECT_MARKERS: &[(&str, ProjectType)] = &[
-    ("Cargo.toml", ProjectType::Rust),
-    ("package.json", ProjectType::JavaScript),
-    ("tsconfig.json", ProjectType::TypeScript),
-    ("deno.json", ProjectType::TypeScript),
-    ("requirements.txt", ProjectType::Python),
-    ("setup.py", ProjectType::Python),
-    ("pyproject.toml", ProjectType::Python),
-    ("Pipfile", ProjectType::Python),
-    ("go.mod", ProjectType::Go),
-    ("pom.xml", ProjectType::Java),
-    ("build.gradle", ProjectType::Java),
-    ("build.gradle.kts", ProjectType::Java),
-    (".csproj", ProjectType::CSharp),
-    (".sln", ProjectType::CSharp),
-    ("CMakeLists.txt", ProjectType::Cpp),
-    ("Makefile", ProjectType::Cpp),
-    ("Gemfile", ProjectType::Ruby),
-    ("composer.json", ProjectType::Php),
-    (".git", ProjectType::Git),
+/// Weight of a config marker rule: as specific as a language's manifest.
+const CUSTOM_WEIGHT: u32 = 40;
+
+/// Markers at or below this weight are generic (a Makefile, a `.git`
+/// directory) and only count when nothing more specific is present.
+const WEAK: u32 = 5;
+
+/// Marker files with how strongly each indicates its type. A `*.ext` entry
+/// matches any file with that extension; a nested path checks that path.
+const PROJECT_MARKERS: &[(&str, ProjectType, u32)] = &[
+    ("Cargo.toml", ProjectType::Rust, 40),
+    ("src-tauri/Cargo.toml", ProjectType::Rust, 30),
+    ("package.json", ProjectType::JavaScript, 20),
+    ("tsconfig.json", ProjectType::TypeScript, 40),
+    ("deno.json", ProjectType::TypeScript, 40),
+    ("requirements.txt", ProjectType::Python, 20),
+    ("setup.py", ProjectType::Python, 30),
+    ("pyproject.toml", ProjectType::Python, 40),
+    ("Pipfile", ProjectType::Python, 30),
+    ("go.mod", ProjectType::Go, 40),
+    ("pom.xml", ProjectType::Java, 40),
+    ("build.gradle", ProjectType::Java, 35),
+    ("build.gradle.kts", ProjectType::Java, 35),
+    ("*.csproj", ProjectType::CSharp, 40),
+    ("*.sln", ProjectType::CSharp, 35),
+    ("CMakeLists.txt", ProjectType::Cpp, 30),
+    ("Makefile", ProjectType::Cpp, WEAK),
+    ("Gemfile", ProjectType::Ruby, 40),
+    ("composer.json", ProjectType::Php, 40),
+    (".git", ProjectType::Git, 1),
 ];
 
+/// The most likely project type of `path`, if it has any markers.
 pub fn detect_project_type(path: &Path) -> Option<ProjectType> {
-    for (marker, project_type) in PROJECT_MARKERS {
-        let marker_path = path.join(marker);
-        if marker_path.exists() {
-            return Some(*project_type);
-        }
+    ranked_types(path, scan_rules()).0.first().copied()
+}
+
+/// Ranks `path`'s types by marker weight and reads its manifests for
+/// frameworks.
+pub fn detect_project(path: &Path) -> Detection {
+    detect_project_with(path, scan_rules())
+}
+
+/// [`detect_project`] with explicit rules instead of the config's.
+pub fn detect_project_with(path: &Path, rules: &ScanRules) -> Detection {
+    let (types, custom) = ranked_types(path, rules);
+    let frameworks = if types.is_empty() && custom.is_empty() {
+        Vec::new()
+    } else {
+        detect_frameworks(path)
+    };
+    Detection {
+        types,
+        custom,
+        frameworks,
     }
-    None
 }
 
 pub fn is_project_directory(path: &Path) -> bool {
-    detect_project_type(path).is_some()
+    let (types, custom) = ranked_types(path, scan_rules());
+    !types.is_empty() || !custom.is_empty()
+}
+
+/// What a marker counts towards: a built-in type, or a config rule's name.
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Builtin(ProjectType),
+    Custom(String),
+}
+
+fn ranked_types(path: &Path, rules: &ScanRules) -> (Vec<ProjectType>, Vec<String>) {
+    // Glob markers need a listing; everything else is a single stat.
+    let names: Vec<String> = std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut scores: Vec<(Kind, u32)> = Vec::new();
+    let mut add = |kind: Kind, weight: u32| match scores.iter_mut().find(|(k, _)| *k == kind) {
+        Some((_, score)) => *score += weight,
+        None => scores.push((kind, weight)),
+    };
+    for (marker, project_type, weight) in PROJECT_MARKERS {
+        let present = match marker.strip_prefix("*.") {
+            Some(ext) => names.iter().any(|n| Path::new(n).extension().is_some_and(|e| e == ext)),
+            None => path.join(marker).exists(),
+        };
+        if present {
+            add(Kind::Builtin(*project_type), *weight);
+        }
+    }
+    for rule in rules.matching_markers(path, &names) {
+        let kind = match ProjectType::from_name(&rule.project_type) {
+            Some(t) => Kind::Builtin(t),
+            None => Kind::Custom(rule.project_type.clone()),
+        };
+        add(kind, CUSTOM_WEIGHT);
+    }
+
+    // A TypeScript project always has a package.json too; listing both adds
+    // nothing.
+    if scores.iter().any(|(k, _)| *k == Kind::Builtin(ProjectType::TypeScript)) {
+        scores.retain(|(k, _)| *k != Kind::Builtin(ProjectType::JavaScript));
+    }
+    if scores.iter().any(|(_, score)| *score > WEAK) {
+        scores.retain(|(_, score)| *score > WEAK);
+    }
+
+    // Ties go to built-ins in display order, then rules in config order.
+    let order = |k: &Kind| match k {
+        Kind::Builtin(t) => ProjectType::ALL.iter().position(|a| a == t),
+        Kind::Custom(_) => None,
+    };
+    scores.sort_by(|(ka, a), (kb, b)| {
+        b.cmp(a).then_with(|| match (order(ka), order(kb)) {
+            (Some(x), Some(y)) => x.cmp(&y),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        })
+    });
+
+    let mut types = Vec::new();
+    let mut custom = Vec::new();
+    for (kind, _) in scores {
+        match kind {
+            Kind::Builtin(t) => types.push(t),
+            Kind::Custom(name) => custom.push(name),
+        }
+    }
+    (types, custom)
+}
+
+fn detect_frameworks(path: &Path) -> Vec<Framework> {
+    let read = |file: &str| std::fs::read_to_string(path.join(file)).unwrap_or_default();
+    let exists = |file: &str| path.join(file).exists();
+    let any_exists = |stem: &str| ["js", "mjs", "cjs", "ts", "mts"]
+        .iter()
+        .any(|ext| exists(&format!("{}.{}", stem, ext)));
+
+    let npm = npm_dependencies(&read("package.json"));
+    let has_npm = |name: &str| npm.iter().any(|d| d == name);
+    let python = [read("requirements.txt"), read("pyproject.toml"), read("Pipfile")].concat();
+    let cargo = [read("Cargo.toml"), read("src-tauri/Cargo.toml")].concat();
+    let jvm = [read("pom.xml"), read("build.gradle"), read("build.gradle.kts")].concat();
+
+    let checks = [
+        (Framework::NextJs, has_npm("next") || any_exists("next.config")),
+        (Framework::Vite, has_npm("vite") || any_exists("vite.config")),
+        (Framework::Django, mentions_package(&python, "django") || exists("manage.py")),
+        (Framework::FastApi, mentions_package(&python, "fastapi")),
+        (Framework::Rails, mentions_package(&read("Gemfile"), "rails")),
+        (Framework::Spring, jvm.contains("org.springframework")),
+        (Framework::Axum, mentions_package(&cargo, "axum")),
+        (
+            Framework::Tauri,
+            exists("src-tauri/tauri.conf.json")
+                || exists("tauri.conf.json")
+                || has_npm("@tauri-apps/api")
+                || has_npm("@tauri-apps/cli")
+                || mentions_package(&cargo, "tauri"),
+        ),
+    ];
+    checks
+        .into_iter()
+        .filter_map(|(framework, found)| found.then_some(framework))
+        .collect()
+}
+
+/// Package names in a package.json's dependency tables.
+fn npm_dependencies(manifest: &str) -> Vec<String> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(manifest) else {
+        return Vec::new();
+    };
+    ["dependencies", "devDependencies", "peerDependencies"]
+        .iter()
+        .filter_map(|key| json.get(key)?.as_object())
+        .flat_map(|deps| deps.keys().cloned())
+        .collect()
+}
+
+/// Whether a manifest names the package `name` as a dependency: at the start
+/// of a line (`django>=4`, `axum = "0.7"`) or opening a quoted string
+/// (`gem "rails"`, `"fastapi[all]"`). The name has to end there, so `django`
+/// doesn't match `djangorestframework`.
+fn mentions_package(manifest: &str, name: &str) -> bool {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+    manifest.lines().any(|line| {
+        let line = line.trim_start().to_lowercase();
+        line.match_indices(name).any(|(i, _)| {
+            let before = line[..i].chars().next_back();
+            let after = line[i + name.len()..].chars().next();
+            matches!(before, None | Some('"' | '\'')) && !after.is_some_and(is_name_char)
+        })
+    })
 }
 
 #[cfg(test)]
@@ -77,16 +356,23 @@ mod tests {
     use std::fs;
     use tempfile::TempDir;
 
+    // The `detect_project_type` and `is_project_directory` wrappers read the
+    // machine's config, so tests go through the built-in rules instead.
+    fn type_of(path: &Path) -> Option<ProjectType> {
+        detect_project_with(path, &ScanRules::default()).primary()
+    }
+
+    fn is_project(path: &Path) -> bool {
+        !detect_project_with(path, &ScanRules::default()).is_empty()
+    }
+
     #[test]
     fn test_detect_rust_project() {
         let temp_dir = TempDir::new().unwrap();
         let cargo_toml = temp_dir.path().join("Cargo.toml");
         fs::write(&cargo_toml, "[package]").unwrap();
 
-        assert_eq!(
-            detect_project_type(temp_dir.path()),
-            Some(ProjectType::Rust)
-        );
+        assert_eq!(type_of(temp_dir.path()), Some(ProjectType::Rust));
     }
 
     #[test]
@@ -95,25 +381,99 @@ mod tests {
         let package_json = temp_dir.path().join("package.json");
         fs::write(&package_json, "{}").unwrap();
 
-        assert_eq!(
-            detect_project_type(temp_dir.path()),
-            Some(ProjectType::JavaScript)
-        );
+        assert_eq!(type_of(temp_dir.path()), Some(ProjectType::JavaScript));
     }
 
     #[test]
     fn test_detect_no_project() {
         let temp_dir = TempDir::new().unwrap();
-        assert_eq!(detect_project_type(temp_dir.path()), None);
+        assert_eq!(type_of(temp_dir.path()), None);
     }
 
     #[test]
     fn test_is_project_directory() {
         let temp_dir = TempDir::new().unwrap();
-        assert!(!is_project_directory(temp_dir.path()));
+        assert!(!is_project(temp_dir.path()));
 
         let cargo_toml = temp_dir.path().join("Cargo.toml");
         fs::write(&cargo_toml, "[package]").unwrap();
-        assert!(is_project_directory(temp_dir.path()));
+        assert!(is_project(temp_dir.path()));
+    }
+
+    #[test]
+    fn test_ranked_types_prefer_specific_markers() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("package.json"), "{}").unwrap();
+        fs::write(root.join("tsconfig.json"), "{}").unwrap();
+        fs::write(root.join("Makefile"), "all:").unwrap();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::create_dir(root.join("src-tauri")).unwrap();
+        fs::write(root.join("src-tauri/Cargo.toml"), "[dependencies]\ntauri = \"2\"\n").unwrap();
+
+        let detection = detect_project_with(root, &ScanRules::default());
+        assert_eq!(detection.types, [ProjectType::TypeScript, ProjectType::Rust]);
+        assert_eq!(detection.frameworks, [Framework::Tauri]);
+        assert_eq!(detection.label(), "TypeScript, Rust · Tauri");
+        assert!(detection.matches("tauri") && detection.matches("rust"));
+
+        // Generic markers still count when they're all there is.
+        let bare = TempDir::new().unwrap();
+        fs::write(bare.path().join("Makefile"), "all:").unwrap();
+        fs::write(bare.path().join("App.csproj"), "").unwrap();
+        assert_eq!(type_of(bare.path()), Some(ProjectType::CSharp));
+        fs::remove_file(bare.path().join("App.csproj")).unwrap();
+        assert_eq!(type_of(bare.path()), Some(ProjectType::Cpp));
+    }
+
+    #[test]
+    fn test_config_marker_rules() {
+        use crate::core::config::MarkerRule;
+        let rule = |pattern: &str, ty: &str| MarkerRule {
+            pattern: pattern.to_string(),
+            project_type: ty.to_string(),
+        };
+        let rules = ScanRules {
+            markers: vec![
+                rule("mix.exs", "Elixir"),
+                rule("*.tf", "Terraform"),
+                rule("rust-toolchain.toml", "rust"),
+            ],
+            ..Default::default()
+        };
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("main.tf"), "").unwrap();
+        fs::write(root.join("Makefile"), "all:").unwrap();
+        let detection = detect_project_with(root, &rules);
+        assert_eq!(detection.custom, ["Terraform"]);
+        assert!(detection.types.is_empty(), "the Makefile is outranked");
+        assert!(detection.matches("terraform"));
+
+        // A built-in name folds into that type.
+        fs::write(root.join("rust-toolchain.toml"), "").unwrap();
+        let detection = detect_project_with(root, &rules);
+        assert_eq!(detection.types, [ProjectType::Rust]);
+        assert_eq!(detection.label(), "Rust, Terraform");
+        assert!(detect_project_with(TempDir::new().unwrap().path(), &rules).is_empty());
+    }
+
+    #[test]
+    fn test_detect_frameworks_from_manifests() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{"dependencies": {"next": "14"}, "devDependencies": {"vite": "5"}}"#,
+        )
+        .unwrap();
+        fs::write(root.join("requirements.txt"), "djangorestframework\nFastAPI[all]>=0.110\n").unwrap();
+        assert_eq!(detect_frameworks(root), [Framework::NextJs, Framework::Vite, Framework::FastApi]);
+
+        assert!(mentions_package("gem \"rails\", \"~> 7\"", "rails"));
+        assert!(mentions_package("[dependencies]\naxum = { version = \"0.7\" }", "axum"));
+        assert!(!mentions_package("description = \"not an axum app\"", "axum"));
+        assert!(!mentions_package("tauri-build = \"2\"", "tauri"));
     }
 }
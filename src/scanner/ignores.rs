@@ -0,0 +1,166 @@
+//! Ignore files for scan and filesystem search
+//!
+//! Walkers carry an [`IgnoreStack`] down the tree: each directory adds its
+//! `.gitignore`, `.ignore` and `.vcodeignore` (all gitignore syntax) on top
+//! of its parent's, and a global `ignore` file in the vcode config directory
+//! sits at the bottom. Like git (and ripgrep), `.gitignore` files only count
+//! inside a git repository, so a dotfiles repo's `*` in `~/.gitignore`
+//! doesn't hide every project under the home directory.
+
+use crate::APP_NAME;
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Per-directory ignore files, in increasing order of precedence.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore", ".vcodeignore"];
+
+/// The ignore rules in effect for one directory of a walk.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreStack {
+    /// Innermost last. Empty (and never extended) when ignore files are off.
+    layers: Vec<Arc<Gitignore>>,
+    enabled: bool,
+    in_git: bool,
+}
+
+impl IgnoreStack {
+    /// The rules for a walk starting at `root`: the global ignore file
+    /// `global` (see [`global_ignore_path`]) plus `root`'s own files. With
+    /// `enabled` false nothing is ever ignored.
+    pub fn new(root: &Path, enabled: bool, global: Option<&Path>) -> Self {
+        if !enabled {
+            return Self::default();
+        }
+        let mut stack = Self {
+            layers: Vec::new(),
+            enabled,
+            in_git: root.ancestors().any(|dir| dir.join(".git").exists()),
+        };
+        if let Some(global) = global {
+            let mut builder = GitignoreBuilder::new(root);
+            builder.add(global);
+            stack.push(builder);
+        }
+        stack.descend(root)
+    }
+
+    /// The rules in effect for `dir` during a walk starting at `root`, for
+    /// picking up a walk part-way down the tree.
+    pub fn for_dir(root: &Path, dir: &Path, enabled: bool, global: Option<&Path>) -> Self {
+        let mut stack = Self::new(root, enabled, global);
+        if let Ok(rel) = dir.strip_prefix(root) {
+            let mut path = root.to_path_buf();
+            for component in rel.components() {
+                path.push(component);
+                stack = stack.descend(&path);
+            }
+        }
+        stack
+    }
+
+    /// The rules for `dir`, a child of the directory these rules are for.
+    pub fn descend(&self, dir: &Path) -> Self {
+        if !self.enabled {
+            return self.clone();
+        }
+        let mut stack = self.clone();
+        stack.in_git = stack.in_git || dir.join(".git").exists();
+
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut any = false;
+        for name in IGNORE_FILES {
+            if *name == ".gitignore" && !stack.in_git {
+                continue;
+            }
+            let file = dir.join(name);
+            if file.is_file() {
+                // Unreadable lines are skipped; the rest of the file applies.
+                builder.add(file);
+                any = true;
+            }
+        }
+        if any {
+            stack.push(builder);
+        }
+        stack
+    }
+
+    /// Whether `path` (a directory when `is_dir`) is ignored. The innermost
+    /// file with an opinion decides, so a nested `!keep/` re-includes.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for layer in self.layers.iter().rev() {
+            match layer.matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+
+    fn push(&mut self, builder: GitignoreBuilder) {
+        if let Ok(gitignore) = builder.build()
+            && !gitignore.is_empty()
+        {
+            self.layers.push(Arc::new(gitignore));
+        }
+    }
+}
+
+/// `~/.config/vcode/ignore`: gitignore patterns applied to every walk.
+pub fn global_ignore_path() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join(APP_NAME).join("ignore"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_ignore_files_stack() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("repo/.git")).unwrap();
+        fs::create_dir_all(root.join("repo/sub")).unwrap();
+        fs::write(root.join(".vcodeignore"), "data/\n").unwrap();
+        fs::write(root.join("repo/.gitignore"), "vendor/\ngen*/\n").unwrap();
+        fs::write(root.join("repo/sub/.ignore"), "!generated/\n").unwrap();
+        let global = temp.path().join("global-ignore");
+        fs::write(&global, "scratch/\n").unwrap();
+
+        let top = IgnoreStack::new(root, true, Some(&global));
+        assert!(top.is_ignored(&root.join("data"), true));
+        assert!(top.is_ignored(&root.join("scratch"), true));
+        assert!(!IgnoreStack::new(root, true, None).is_ignored(&root.join("scratch"), true));
+        assert!(!top.is_ignored(&root.join("repo"), true));
+
+        let repo = top.descend(&root.join("repo"));
+        assert!(repo.is_ignored(&root.join("repo/vendor"), true));
+        assert!(repo.is_ignored(&root.join("repo/data"), true));
+
+        let sub = repo.descend(&root.join("repo/sub"));
+        assert!(!sub.is_ignored(&root.join("repo/sub/generated"), true));
+        assert!(sub.is_ignored(&root.join("repo/sub/gen2"), true));
+
+        let off = IgnoreStack::new(root, false, Some(&global));
+        assert!(!off.descend(&root.join("repo")).is_ignored(&root.join("repo/vendor"), true));
+    }
+
+    #[test]
+    fn test_gitignore_needs_a_repository() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("home");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join(".gitignore"), "*\n").unwrap();
+        // The temp dir isn't inside a git checkout on CI or here.
+        if temp.path().ancestors().any(|d| d.join(".git").exists()) {
+            return;
+        }
+        let stack = IgnoreStack::new(&root, true, None);
+        assert!(!stack.is_ignored(&root.join("project"), true));
+    }
+}
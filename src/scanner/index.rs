@@ -0,0 +1,377 @@
+//! Persistent directory index
+//!
+//! Filesystem search (`search --fs`, `add --find`, `project:sub`) would
+//! otherwise walk up to seven levels of the project roots or `$HOME` on
+//! every call. The index records each directory under those roots once
+//! (path, name, mtime and detected type) in `index.json` next to
+//! `projects.json`, and searches filter that list instead.
+//!
+//! A directory's mtime changes whenever an entry is added to, removed from
+//! or renamed in it, so keeping the index current costs one stat per indexed
+//! directory: unchanged directories keep their children, and a changed one
+//! only has its own listing re-read (new subdirectories are walked, vanished
+//! ones dropped). Changes that never touch a listing, like an edited ignore
+//! file or new skip dirs, are only picked up by `vcode index rebuild`, which
+//! is why an index older than [`MAX_AGE`] counts as stale.
+//!
+//! Each root is indexed as its own tree, even one nested in another (a
+//! project root under `$HOME`): depth and the hidden-directory rule count
+//! from where a search starts, so only a tree rooted exactly there gives the
+//! same answer a walk would.
+
+use super::detector::detect_project_with;
+use super::ignores::IgnoreStack;
+use super::rules::ScanRules;
+use super::scanner::SEARCH_DEPTH;
+use super::walk::{Visit, walk_from};
+use crate::core::project::{ensure_data_dir, get_index_path, now_secs};
+use crate::core::storage::{FileLock, write_atomic};
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const INDEX_VERSION: u32 = 2;
+
+/// How long after a full build the index is trusted, in seconds.
+pub const MAX_AGE: u64 = 7 * 24 * 60 * 60;
+
+/// One directory in the index.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IndexedDir {
+    pub path: PathBuf,
+    pub name: String,
+    /// Modification time in nanoseconds since the epoch.
+    pub mtime: u64,
+    /// Levels below the index root it was found under (the root is 0).
+    pub depth: u32,
+    /// Detected type label, for directories that look like projects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_type: Option<String>,
+}
+
+/// The directories under one root, as a search walk from it sees them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct IndexedTree {
+    pub root: PathBuf,
+    /// Sorted by path, so a directory's subtree directly follows it.
+    pub dirs: Vec<IndexedDir>,
+}
+
+/// The contents of `index.json`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DirIndex {
+    pub version: u32,
+    /// One tree per indexed root.
+    pub trees: Vec<IndexedTree>,
+    /// When the index was last built from scratch.
+    pub built_at: u64,
+    /// When the index was last brought up to date.
+    pub refreshed_at: u64,
+}
+
+impl DirIndex {
+    /// Walks `roots` from scratch, skipping and detecting projects by
+    /// `rules`. Roots that don't exist are left out.
+    pub fn build(roots: &[PathBuf], rules: &ScanRules) -> Self {
+        // Building or refreshing the index is an explicit (or already
+        // indexed) walk, so it isn't cut short by the search walk limits.
+        let rules = rules.unlimited();
+        let now = now_secs();
+        Self {
+            version: INDEX_VERSION,
+            trees: existing(roots).into_iter().map(|root| IndexedTree::build(root, &rules)).collect(),
+            built_at: now,
+            refreshed_at: now,
+        }
+    }
+
+    /// Brings the index up to date with the filesystem using directory
+    /// mtimes. Returns whether anything changed.
+    pub fn refresh(&mut self, rules: &ScanRules) -> bool {
+        let rules = rules.unlimited();
+        let mut changed = false;
+        for tree in &mut self.trees {
+            changed |= tree.refresh(&rules);
+        }
+        self.refreshed_at = now_secs();
+        changed
+    }
+
+    /// The indexed roots.
+    pub fn roots(&self) -> impl Iterator<Item = &Path> {
+        self.trees.iter().map(|t| t.root.as_path())
+    }
+
+    /// Whether searches from `base` can be answered from this index: only
+    /// when `base` is itself an indexed root.
+    pub fn covers(&self, base: &Path) -> bool {
+        self.roots().any(|root| root == base)
+    }
+
+    /// Built by another version of vcode, or too long ago to trust.
+    pub fn is_stale(&self) -> bool {
+        self.version != INDEX_VERSION || now_secs().saturating_sub(self.built_at) > MAX_AGE
+    }
+
+    /// Indexed directories strictly below the root `base`, at most
+    /// `max_depth` levels down. Nothing when `base` isn't a root.
+    pub fn dirs_under(&self, base: &Path, max_depth: u32) -> impl Iterator<Item = &IndexedDir> {
+        self.trees
+            .iter()
+            .filter(move |t| t.root == base)
+            .flat_map(|t| &t.dirs)
+            .filter(move |dir| dir.depth > 0 && dir.depth <= max_depth)
+    }
+
+    /// How many directories are indexed, counting each tree separately.
+    pub fn dir_count(&self) -> usize {
+        self.trees.iter().map(|t| t.dirs.len()).sum()
+    }
+
+    /// How many indexed directories look like projects.
+    pub fn project_count(&self) -> usize {
+        self.trees.iter().flat_map(|t| &t.dirs).filter(|d| d.project_type.is_some()).count()
+    }
+
+    fn save(&self) -> Result<()> {
+        ensure_data_dir()?;
+        let json = serde_json::to_string(self).map_err(Error::Serialize)?;
+        write_atomic(&get_index_path()?, json.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl IndexedTree {
+    fn build(root: PathBuf, rules: &ScanRules) -> Self {
+        let name = root.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let mut dirs: Vec<IndexedDir> = indexed(&root, &name, 0, rules).into_iter().collect();
+        let ignores = IgnoreStack::new(&root, true, rules.global_ignore.as_deref());
+        dirs.extend(index_subtree(&root, 0, ignores, rules));
+        dirs.sort_by(|a, b| a.path.cmp(&b.path));
+        Self { root, dirs }
+    }
+
+    fn refresh(&mut self, rules: &ScanRules) -> bool {
+        let mut children: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+        for dir in &self.dirs {
+            if let Some(parent) = dir.path.parent() {
+                children.entry(parent.to_path_buf()).or_default().push(dir.path.clone());
+            }
+        }
+
+        let mut changed = false;
+        let mut removed: Vec<PathBuf> = Vec::new();
+        let mut added = Vec::new();
+        for i in 0..self.dirs.len() {
+            if removed.iter().any(|r| self.dirs[i].path.starts_with(r)) {
+                continue;
+            }
+            let Some(mtime) = mtime_of(&self.dirs[i].path) else {
+                removed.push(self.dirs[i].path.clone());
+                continue;
+            };
+            if mtime == self.dirs[i].mtime {
+                continue;
+            }
+            changed = true;
+
+            let dir = &mut self.dirs[i];
+            dir.mtime = mtime;
+            dir.project_type = type_label(&dir.path, rules);
+            let (path, depth) = (dir.path.clone(), dir.depth);
+            let ignores = IgnoreStack::for_dir(&self.root, &path, true, rules.global_ignore.as_deref());
+
+            let listed = list_children(&path, depth, &ignores, rules);
+            let known = children.get(&path).map(Vec::as_slice).unwrap_or_default();
+            for (child, name) in &listed {
+                if !known.contains(child) {
+                    added.extend(indexed(child, name, depth + 1, rules));
+                    added.extend(index_subtree(child, depth + 1, ignores.descend(child), rules));
+                }
+            }
+            let listed: HashSet<&PathBuf> = listed.iter().map(|(p, _)| p).collect();
+            removed.extend(known.iter().filter(|c| !listed.contains(c)).cloned());
+        }
+
+        if !removed.is_empty() {
+            changed = true;
+        }
+        self.dirs.retain(|d| !removed.iter().any(|r| d.path.starts_with(r)));
+        self.dirs.extend(added);
+        self.dirs.sort_by(|a, b| a.path.cmp(&b.path));
+        changed
+    }
+}
+
+/// Reads `index.json`. A missing file is `None`.
+pub fn load_index() -> Result<Option<DirIndex>> {
+    let path = get_index_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = read_to_string(&path)?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|source| Error::Parse { path, source })
+}
+
+/// Builds the index over `roots` from scratch and saves it.
+pub fn rebuild_index(roots: &[PathBuf], rules: &ScanRules) -> Result<DirIndex> {
+    ensure_data_dir()?;
+    let _lock = FileLock::acquire(&get_index_path()?.with_extension("lock"))?;
+    let index = DirIndex::build(roots, rules);
+    index.save()?;
+    Ok(index)
+}
+
+/// The index, brought up to date, when it can answer a search from
+/// `base`. `None` when it's missing, unreadable, stale or doesn't have
+/// `base` as a root; the caller walks instead.
+pub fn index_for(base: &Path, rules: &ScanRules) -> Option<DirIndex> {
+    let _lock = FileLock::acquire(&get_index_path().ok()?.with_extension("lock")).ok()?;
+    let mut index = load_index().ok().flatten()?;
+    if index.is_stale() || !index.covers(base) {
+        return None;
+    }
+    if index.refresh(rules) {
+        // A failed save only costs the next search the same refresh.
+        let _ = index.save();
+    }
+    Some(index)
+}
+
+/// `roots` that exist, each once.
+fn existing(roots: &[PathBuf]) -> Vec<PathBuf> {
+    let mut out: Vec<PathBuf> = Vec::new();
+    for root in roots.iter().filter(|r| r.is_dir()) {
+        if !out.contains(root) {
+            out.push(root.clone());
+        }
+    }
+    out
+}
+
+fn indexed(path: &Path, name: &str, depth: u32, rules: &ScanRules) -> Option<IndexedDir> {
+    Some(IndexedDir {
+        path: path.to_path_buf(),
+        name: name.to_string(),
+        mtime: mtime_of(path)?,
+        depth,
+        project_type: type_label(path, rules),
+    })
+}
+
+fn type_label(path: &Path, rules: &ScanRules) -> Option<String> {
+    let detection = detect_project_with(path, rules);
+    (!detection.is_empty()).then(|| detection.label())
+}
+
+fn mtime_of(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64)
+}
+
+/// Hidden directories are indexed only directly under a root, matching what
+/// a search walk from that root visits.
+fn hidden_below_top(name: &str, depth: u32) -> bool {
+    name.starts_with('.') && depth > 1
+}
+
+/// Every directory below `dir`, which sits `depth` levels under its root.
+fn index_subtree(dir: &Path, depth: u32, ignores: IgnoreStack, rules: &ScanRules) -> Vec<IndexedDir> {
+    if depth >= SEARCH_DEPTH {
+        return Vec::new();
+    }
+    let walked = walk_from(dir, ignores, SEARCH_DEPTH - depth, rules, |d| {
+        let depth = depth + d.depth;
+        if hidden_below_top(d.name, depth) {
+            return Visit { item: None, descend: false };
+        }
+        Visit {
+            item: indexed(d.path, d.name, depth, rules),
+            descend: true,
+        }
+    });
+    walked.map(|w| w.items).unwrap_or_default()
+}
+
+/// The subdirectories of `dir` an index walk would enter.
+fn list_children(dir: &Path, depth: u32, ignores: &IgnoreStack, rules: &ScanRules) -> Vec<(PathBuf, String)> {
+    if depth >= SEARCH_DEPTH {
+        return Vec::new();
+    }
+    let walked = walk_from(dir, ignores.clone(), 1, rules, |d| Visit {
+        item: (!hidden_below_top(d.name, depth + 1)).then(|| (d.path.to_path_buf(), d.name.to_string())),
+        descend: false,
+    });
+    walked.map(|w| w.items).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn paths(index: &DirIndex, root: &Path) -> Vec<String> {
+        index.trees[0]
+            .dirs
+            .iter()
+            .filter_map(|d| d.path.strip_prefix(root).ok())
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_build_records_dirs_and_types() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for dir in ["api/src", "web/node_modules/x", ".dots/inner", "a/.hidden"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("api/Cargo.toml"), "").unwrap();
+
+        let roots = [root.to_path_buf(), root.join("api"), root.join("nope")];
+        let index = DirIndex::build(&roots, &ScanRules::default());
+        assert_eq!(index.roots().collect::<Vec<_>>(), [root, root.join("api").as_path()]);
+        assert_eq!(paths(&index, root), ["", ".dots", ".dots/inner", "a", "api", "api/src", "web"]);
+
+        let api = index.trees[0].dirs.iter().find(|d| d.name == "api").unwrap();
+        assert_eq!((api.depth, api.project_type.as_deref()), (1, Some("Rust")));
+
+        let under_api: Vec<_> = index.dirs_under(&root.join("api"), 7).map(|d| d.name.as_str()).collect();
+        assert_eq!(under_api, ["src"]);
+        assert!(index.covers(&root.join("api")) && !index.covers(&root.join("web")));
+        let top: Vec<_> = index.dirs_under(root, 1).map(|d| d.name.as_str()).collect();
+        assert_eq!(top, [".dots", "a", "api", "web"]);
+    }
+
+    #[test]
+    fn test_refresh_follows_directory_mtimes() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for dir in ["keep/deep", "gone/child", "parent"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let rules = ScanRules::default();
+        let mut index = DirIndex::build(&[root.to_path_buf()], &rules);
+        assert!(!index.refresh(&rules), "nothing changed yet");
+
+        fs::remove_dir_all(root.join("gone")).unwrap();
+        fs::create_dir_all(root.join("parent/new/nested")).unwrap();
+        fs::write(root.join("parent/new/package.json"), "{}").unwrap();
+        // Make sure the mtimes differ even on coarse-grained filesystems.
+        for dir in [root.to_path_buf(), root.join("parent")] {
+            let file = fs::File::open(&dir).unwrap();
+            file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(5)).unwrap();
+        }
+
+        assert!(index.refresh(&rules));
+        assert_eq!(paths(&index, root), ["", "keep", "keep/deep", "parent", "parent/new", "parent/new/nested"]);
+        let new = index.trees[0].dirs.iter().find(|d| d.name == "new").unwrap();
+        assert_eq!((new.depth, new.project_type.as_deref()), (2, Some("JavaScript")));
+    }
+}
@@ -0,0 +1,121 @@
+//! Package names from project manifests
+//!
+//! A project's directory can be renamed freely, but the name in its
+//! manifest usually stays put, which makes it a good way to name a project
+//! and to recognise one that moved. Like the workspace readers, these only
+//! pick the one field they need out of each file.
+
+use super::workspace::{strip_comment, unquote};
+use std::fs::read_to_string;
+use std::path::Path;
+
+/// Reads the package name out of a manifest's contents.
+type NameReader = fn(&str) -> Option<String>;
+
+/// Manifests a package name is read from, in order of preference, with the
+/// reader for each.
+const MANIFESTS: &[(&str, NameReader)] = &[
+    ("Cargo.toml", cargo_package_name),
+    ("package.json", package_json_name),
+    ("pyproject.toml", pyproject_name),
+    ("go.mod", go_module_name),
+];
+
+/// The package name declared by `dir`'s manifest, and the manifest's file
+/// name: `name` in `Cargo.toml`'s `[package]`, `package.json`,
+/// `pyproject.toml`'s `[project]` or `[tool.poetry]`, or the module path in
+/// `go.mod`, as written.
+pub fn manifest_name(dir: &Path) -> Option<(String, &'static str)> {
+    MANIFESTS.iter().find_map(|&(file, read)| {
+        let name = read(&read_to_string(dir.join(file)).ok()?)?;
+        (!name.is_empty()).then_some((name, file))
+    })
+}
+
+/// The manifest file called `file`, as the `'static` name [`manifest_name`]
+/// reports it under.
+pub(super) fn manifest_file(file: &str) -> Option<&'static str> {
+    MANIFESTS.iter().map(|&(name, _)| name).find(|&name| name == file)
+}
+
+/// Just the name from [`manifest_name`].
+pub fn package_name(dir: &Path) -> Option<String> {
+    manifest_name(dir).map(|(name, _)| name)
+}
+
+/// A package name without its npm scope (`@acme/web`), or a Go module path
+/// without its host, owner and major-version suffix
+/// (`github.com/acme/api/v2`): what people call the project.
+pub fn short_package_name(name: &str) -> &str {
+    let mut segments = name.rsplit('/');
+    let last = segments.next().unwrap_or(name);
+    let is_major_version = last
+        .strip_prefix('v')
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+    match segments.next() {
+        Some(parent) if is_major_version => parent,
+        _ => last,
+    }
+}
+
+fn cargo_package_name(manifest: &str) -> Option<String> {
+    toml_table_name(manifest, &["[package]"])
+}
+
+fn pyproject_name(manifest: &str) -> Option<String> {
+    toml_table_name(manifest, &["[project]", "[tool.poetry]"])
+}
+
+/// `name = "..."` inside the first of `tables` that has one.
+fn toml_table_name(manifest: &str, tables: &[&str]) -> Option<String> {
+    let mut in_table = false;
+    for line in manifest.lines() {
+        let line = strip_comment(line, '#').trim();
+        if line.starts_with('[') {
+            in_table = tables.contains(&line);
+            continue;
+        }
+        if in_table
+            && let Some((key, value)) = line.split_once('=')
+            && key.trim() == "name"
+        {
+            return Some(unquote(value.trim()).to_string());
+        }
+    }
+    None
+}
+
+/// The path on the `module` line.
+fn go_module_name(manifest: &str) -> Option<String> {
+    manifest
+        .lines()
+        .map(|line| strip_comment(line, '/').trim())
+        .find_map(|line| line.strip_prefix("module "))
+        .map(|module| unquote(module.trim()).to_string())
+}
+
+fn package_json_name(manifest: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(manifest).ok()?;
+    json.get("name")?.as_str().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_name_parsers() {
+        let cargo = "[workspace]\nname = \"nope\"\n\n[package]\nname = \"web-portal\" # the app\nversion = \"0.1.0\"\n";
+        assert_eq!(cargo_package_name(cargo).as_deref(), Some("web-portal"));
+        assert_eq!(cargo_package_name("[workspace]\nmembers = []\n"), None);
+        assert_eq!(package_json_name(r#"{ "name": "@acme/web", "private": true }"#).as_deref(), Some("@acme/web"));
+        let poetry = "[build-system]\nrequires = []\n\n[tool.poetry]\nname = 'etl'\n";
+        assert_eq!(pyproject_name(poetry).as_deref(), Some("etl"));
+        let gomod = "// api service\nmodule github.com/acme/api/v2\n\ngo 1.22\n";
+        assert_eq!(go_module_name(gomod).as_deref(), Some("github.com/acme/api/v2"));
+
+        assert_eq!(short_package_name("github.com/acme/api/v2"), "api");
+        assert_eq!(short_package_name("@acme/web"), "web");
+        assert_eq!(short_package_name("v8"), "v8");
+    }
+}
@@ -3,14 +3,51 @@
 //! This module provides functionality for:
 //! - Detecting project types from marker files (detector.rs)
 //! - Scanning directories to find projects (scanner.rs)
+//! - Reading workspace manifests for monorepo members (workspace.rs)
+//! - Package names from manifests (manifest.rs)
+//! - Naming strategies for found projects (naming.rs)
+//! - User-configured markers and skip dirs (rules.rs)
+//! - `.gitignore`/`.ignore`/`.vcodeignore` handling for walks (ignores.rs)
+//! - The parallel, bounded directory walker (walk.rs)
+//! - The persistent directory index behind filesystem search (index.rs)
+//! - inotify watching for `vcode watch`, Linux only (watcher.rs)
+//! - Reconciling the registry with the filesystem (sync.rs)
 //! - Bulk project operations
 
 pub mod detector;
+pub mod ignores;
+pub mod index;
+pub mod manifest;
+pub mod naming;
+pub mod rules;
+#[allow(clippy::module_inception)]
 pub mod scanner;
+pub mod sync;
+pub mod walk;
+#[cfg(target_os = "linux")]
+pub mod watcher;
+pub mod workspace;
 
 // Re-export commonly used items
-pub use detector::{ProjectType, detect_project_type, is_project_directory};
+pub use detector::{
+    Detection, Framework, ProjectType, detect_project, detect_project_type, detect_project_with,
+    is_project_directory,
+};
+pub use ignores::{IgnoreStack, global_ignore_path};
+pub use index::{DirIndex, IndexedDir, IndexedTree, index_for, load_index, rebuild_index};
+pub use rules::{ScanRules, scan_rules};
+pub use manifest::{manifest_name, package_name, short_package_name};
+pub use naming::{NameSource, project_name};
+pub use sync::{
+    Identity, MoveMatch, SyncApplied, SyncMissing, SyncMove, SyncPlan, apply_sync, best_match, plan_sync,
+    select_sync_changes,
+};
+pub use walk::{Stopped, Walked};
+#[cfg(target_os = "linux")]
+pub use watcher::{WatchEvent, Watcher};
+pub use workspace::workspace_members;
 pub use scanner::{
-    DirectoryMatch, FilterMode, FoundProject, NameMatch, add_projects,
-    interactive_select_projects, scan_projects, search_directories, search_directory_by_name,
+    DirectoryMatch, FilterMode, FoundProject, NameMatch, SEARCH_DEPTH, add_projects, collect_members,
+    find_projects, interactive_select_projects, resolve_subdir, scan_projects, search_directories,
+    search_directory_by_name,
 };
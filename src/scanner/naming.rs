@@ -0,0 +1,146 @@
+//! Naming found projects
+//!
+//! A directory's name is often a poor project name: clones get checked out
+//! as `frontend/` or `app/`, and every company has a dozen `server`s. A
+//! [`NameStrategy`] picks the name from somewhere else, and the
+//! [`NameSource`] it comes back with says where, so review lists can show it.
+
+use super::manifest::{manifest_file, manifest_name, short_package_name};
+use crate::core::git::{origin_url, remote_repo};
+use crate::core::project::{NameStrategy, path_basename};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::path::Path;
+
+/// Where a project's name came from. Stored (in the review queue) as
+/// `dirname`, `git-remote`, `parent/dir`, `member` or the manifest's file
+/// name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameSource {
+    #[default]
+    Dirname,
+    /// The manifest file it was read from.
+    Manifest(&'static str),
+    GitRemote,
+    ParentDir,
+    /// A workspace member, named after its workspace.
+    Member,
+}
+
+impl fmt::Display for NameSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameSource::Dirname => f.write_str("directory name"),
+            NameSource::Manifest(file) => f.write_str(file),
+            NameSource::GitRemote => f.write_str("git remote"),
+            NameSource::ParentDir => f.write_str("parent/dir"),
+            NameSource::Member => f.write_str("workspace member"),
+        }
+    }
+}
+
+impl From<NameSource> for String {
+    fn from(source: NameSource) -> Self {
+        match source {
+            NameSource::Dirname => "dirname",
+            NameSource::Manifest(file) => file,
+            NameSource::GitRemote => "git-remote",
+            NameSource::ParentDir => "parent/dir",
+            NameSource::Member => "member",
+        }
+        .to_string()
+    }
+}
+
+impl From<String> for NameSource {
+    /// Unknown values read back as [`NameSource::Dirname`].
+    fn from(stored: String) -> Self {
+        match stored.as_str() {
+            "git-remote" => NameSource::GitRemote,
+            "parent/dir" => NameSource::ParentDir,
+            "member" => NameSource::Member,
+            file => manifest_file(file).map_or(NameSource::Dirname, NameSource::Manifest),
+        }
+    }
+}
+
+// By hand: derived impls would borrow the `&'static str` from the input.
+impl Serialize for NameSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&String::from(*self))
+    }
+}
+
+impl<'de> Deserialize<'de> for NameSource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(NameSource::from)
+    }
+}
+
+/// The name `strategy` gives the project in `dir`, falling back to the
+/// directory's name when the strategy finds nothing there.
+pub fn project_name(dir: &Path, strategy: NameStrategy) -> (String, NameSource) {
+    let named = match strategy {
+        NameStrategy::Dirname => None,
+        NameStrategy::Manifest => manifest_name(dir)
+            .map(|(name, file)| (short_package_name(&name).to_string(), NameSource::Manifest(file))),
+        NameStrategy::GitRemote => origin_url(dir)
+            .and_then(|url| remote_repo(&url))
+            .map(|repo| (repo, NameSource::GitRemote)),
+        NameStrategy::ParentDir => dir
+            .parent()
+            .map(path_basename)
+            .filter(|parent| !parent.is_empty() && parent != "/")
+            .map(|parent| (format!("{}/{}", parent, path_basename(dir)), NameSource::ParentDir)),
+    };
+    named
+        .filter(|(name, _)| !name.is_empty() && !name.contains(char::is_whitespace))
+        .unwrap_or_else(|| (path_basename(dir), NameSource::Dirname))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_project_name_strategies() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("clients/frontend");
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        fs::write(dir.join("package.json"), r#"{ "name": "@acme/portal" }"#).unwrap();
+        fs::write(
+            dir.join(".git/config"),
+            "[remote \"origin\"]\n\turl = git@github.com:acme/web-portal.git\n",
+        )
+        .unwrap();
+
+        let name = |strategy| project_name(&dir, strategy);
+        assert_eq!(name(NameStrategy::Dirname), ("frontend".to_string(), NameSource::Dirname));
+        assert_eq!(
+            name(NameStrategy::Manifest),
+            ("portal".to_string(), NameSource::Manifest("package.json"))
+        );
+        assert_eq!(
+            name(NameStrategy::GitRemote),
+            ("acme/web-portal".to_string(), NameSource::GitRemote)
+        );
+        assert_eq!(
+            name(NameStrategy::ParentDir),
+            ("clients/frontend".to_string(), NameSource::ParentDir)
+        );
+
+        let bare = temp.path().join("bare");
+        fs::create_dir(&bare).unwrap();
+        assert_eq!(
+            project_name(&bare, NameStrategy::Manifest),
+            ("bare".to_string(), NameSource::Dirname),
+            "falls back to the directory name"
+        );
+
+        for source in [NameSource::Manifest("go.mod"), NameSource::ParentDir, NameSource::Dirname] {
+            assert_eq!(NameSource::from(String::from(source)), source);
+        }
+    }
+}
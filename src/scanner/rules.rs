@@ -0,0 +1,108 @@
+//! User-configured scan rules
+//!
+//! `config.json` can add project markers (`markers`), adjust the built-in
+//! skip list (`skip_dirs`) and bound how much a walk may read
+//! (`walk_limits`); the global ignore file sits next to it. [`scan_rules`]
+//! reads all of that once per process, and turns on the directory index.
+//! Scans, searches, the index and the watcher take the rules as a
+//! parameter, so tests pass their own instead of getting whatever the
+//! machine has configured.
+
+use super::ignores::global_ignore_path;
+use super::scanner::SKIP_DIRS;
+use super::workspace::wildcard_match;
+use crate::core::config::{Config, MarkerRule, SkipDirs, WalkLimits, get_config};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Default)]
+pub struct ScanRules {
+    pub markers: Vec<MarkerRule>,
+    pub skip_dirs: SkipDirs,
+    pub limits: WalkLimits,
+    /// The global ignore file, if there is one.
+    pub global_ignore: Option<PathBuf>,
+    /// Whether searches may be answered from the directory index.
+    pub use_index: bool,
+}
+
+impl ScanRules {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            markers: config.markers.clone(),
+            skip_dirs: config.skip_dirs.clone(),
+            limits: config.walk_limits,
+            global_ignore: None,
+            use_index: false,
+        }
+    }
+
+    /// These rules without walk limits, for walks that have to be complete.
+    pub fn unlimited(&self) -> Self {
+        Self {
+            limits: WalkLimits {
+                max_entries: 0,
+                timeout_secs: 0,
+            },
+            ..self.clone()
+        }
+    }
+
+    /// Whether scans should stay out of directories named `dir_name`.
+    pub fn skips(&self, dir_name: &str) -> bool {
+        let listed = |list: &[String]| list.iter().any(|d| d == dir_name);
+        if listed(&self.skip_dirs.add) {
+            return true;
+        }
+        SKIP_DIRS.contains(&dir_name) && !listed(&self.skip_dirs.remove)
+    }
+
+    /// The marker rules that match inside `dir`. `names` is the directory's
+    /// listing, used for glob patterns.
+    pub fn matching_markers<'a>(&'a self, dir: &Path, names: &[String]) -> impl Iterator<Item = &'a MarkerRule> {
+        self.markers.iter().filter(move |rule| {
+            if rule.pattern.contains('*') && !rule.pattern.contains('/') {
+                names.iter().any(|n| wildcard_match(&rule.pattern, n))
+            } else {
+                dir.join(&rule.pattern).exists()
+            }
+        })
+    }
+}
+
+/// The rules from `config.json` (none when there's no config yet), with the
+/// global ignore file and the directory index.
+pub fn scan_rules() -> &'static ScanRules {
+    static RULES: OnceLock<ScanRules> = OnceLock::new();
+    RULES.get_or_init(|| {
+        let rules = get_config()
+            .map(|c| ScanRules::from_config(&c))
+            .unwrap_or_default();
+        ScanRules {
+            global_ignore: global_ignore_path().filter(|p| p.is_file()),
+            use_index: true,
+            ..rules
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skip_dirs_add_and_remove() {
+        let rules = ScanRules {
+            skip_dirs: SkipDirs {
+                add: vec!["vendor".to_string()],
+                remove: vec!["go".to_string(), "bin".to_string()],
+            },
+            ..Default::default()
+        };
+        assert!(rules.skips("vendor"));
+        assert!(rules.skips("node_modules"));
+        assert!(!rules.skips("go"));
+        assert!(!rules.skips("bin"));
+        assert!(ScanRules::default().skips("go"));
+    }
+}
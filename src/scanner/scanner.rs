@@ -7,19 +7,28 @@
 //! - Interactively select projects to add
 //! - Add multiple projects at once
 
-use super::detector::{ProjectType, detect_project_type};
-use crate::core::project::set_project;
+use super::detector::{Detection, detect_project_with};
+use super::index::{DirIndex, index_for};
+use super::naming::NameSource;
+use super::rules::{ScanRules, scan_rules};
+use super::walk::{Visit, Walked, walk};
+use super::workspace::workspace_members;
+use crate::core::matcher::fuzzy_score;
+use crate::core::project::{ConflictStrategy, Insertion, ProjectEntry, insert_project, update_projects};
+use std::collections::HashMap;
+use crate::error::{Error, Result};
 use crate::ui::logger::{LogType, log};
+use colored::Colorize;
 use dialoguer::{MultiSelect, theme::ColorfulTheme};
-use std::fs::read_dir;
 use std::path::{Path, PathBuf};
 
 // =============================================================================
 // Constants
 // =============================================================================
 
-/// Directories to always skip (build artifacts, dependencies, system dirs)
-const SKIP_DIRS: &[&str] = &[
+/// Directories to skip (build artifacts, dependencies, system dirs). The
+/// config's `skip_dirs` can add to or remove from this, see [`ScanRules::skips`].
+pub(super) const SKIP_DIRS: &[&str] = &[
     // Build artifacts & dependencies
     "node_modules",
     "__pycache__",
@@ -93,21 +102,27 @@ const SKIP_DIRS: &[&str] = &[
     "Templates",
 ];
 
+/// How many levels below its base a filesystem search looks.
+pub const SEARCH_DEPTH: u32 = 7;
+
 /// Represents a project found during directory scanning
 #[derive(Debug, Clone)]
 pub struct FoundProject {
     pub name: String,
     pub path: PathBuf,
-    pub project_type: Option<ProjectType>,
+    pub detection: Detection,
+    /// Tags to give it on top of the ones passed to [`add_projects`], e.g.
+    /// the default tags of the root it was found under.
+    pub tags: Vec<String>,
+    /// Where `name` came from, shown when reviewing.
+    pub name_source: NameSource,
 }
 
 impl FoundProject {
-    /// Returns a formatted display name with project type
+    /// Returns a formatted display name with the detected types and
+    /// frameworks, e.g. `app (TypeScript, Rust · Tauri, Vite)`
     pub fn display_name(&self) -> String {
-        match &self.project_type {
-            Some(ptype) => format!("{} ({})", self.name, ptype.name()),
-            None => format!("{} (Unknown)", self.name),
-        }
+        format!("{} ({})", self.name, self.detection.label())
     }
 }
 
@@ -124,38 +139,135 @@ pub enum FilterMode {
 // Public API - Scanning
 // =============================================================================
 
-/// Scans a directory recursively to find projects
+/// Scans a directory recursively to find projects, in parallel and within
+/// the walk limits of `rules`
 ///
 /// # Arguments
 /// * `base_path` - Directory to start scanning from
 /// * `target_depth` - How many levels deep to scan (1 = immediate children only)
 /// * `filter_mode` - Whether to detect projects automatically or include all directories
+/// * `members` - Also list workspace members of found projects, as `repo/member`
+/// * `respect_ignore` - Skip directories matched by `.gitignore`, `.ignore`,
+///   `.vcodeignore` and the global ignore file
+/// * `rules` - Skip list, markers and walk limits, normally [`scan_rules`]
 ///
 /// # Returns
-/// Vector of found projects, or error if base path is invalid
+/// Found projects sorted by path (partial if the walk was stopped early), or
+/// error if base path is invalid
 ///
 /// # Example
 /// ```ignore
-/// let projects = scan_projects(Path::new("/home/user/projects"), 1, FilterMode::Auto)?;
+/// let projects = scan_projects(Path::new("/home/user/projects"), 1, FilterMode::Auto, true, true, scan_rules())?.items;
 /// ```
 pub fn scan_projects(
     base_path: &Path,
     target_depth: u32,
     filter_mode: FilterMode,
-) -> Result<Vec<FoundProject>, Box<dyn std::error::Error>> {
-    if !base_path.exists() || !base_path.is_dir() {
-        return Err("Base path doesn't exist or is not a directory".into());
+    members: bool,
+    respect_ignore: bool,
+    rules: &ScanRules,
+) -> Result<Walked<FoundProject>> {
+    if !base_path.is_dir() {
+        return Err(Error::InvalidPath {
+            path: base_path.to_path_buf(),
+            reason: "does not exist or is not a directory",
+        });
     }
 
-    let mut found_projects = Vec::new();
-    traverse_and_collect(base_path, target_depth, 1, &mut found_projects, filter_mode)?;
+    let mut walked = walk(base_path, target_depth, respect_ignore, rules, |dir| {
+        if dir.depth < target_depth {
+            return Visit { item: None, descend: true };
+        }
+        let detection = detect_project_with(dir.path, rules);
+        let include = match filter_mode {
+            FilterMode::Auto => !detection.is_empty(),
+            FilterMode::All => true,
+        };
+        Visit {
+            item: include.then(|| FoundProject {
+                name: dir.name.to_string(),
+                path: dir.path.to_path_buf(),
+                detection,
+                tags: Vec::new(),
+                name_source: NameSource::Dirname,
+            }),
+            descend: false,
+        }
+    })?;
+    walked.items.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if members {
+        walked.items = walked
+            .items
+            .into_iter()
+            .flat_map(|project| {
+                let members = collect_members(&project, rules);
+                std::iter::once(project).chain(members)
+            })
+            .collect();
+    }
 
-    Ok(found_projects)
+    Ok(walked)
 }
 
-/// Checks if a directory should be skipped during scanning
-pub fn should_skip_dir(dir_name: &str) -> bool {
-    SKIP_DIRS.contains(&dir_name)
+/// Finds projects at any depth down to `max_depth` below `base_path`,
+/// without looking inside the projects themselves. Unlike [`scan_projects`],
+/// which expects projects at one level, this is for finding a project that
+/// could have been moved anywhere.
+pub fn find_projects(
+    base_path: &Path,
+    max_depth: u32,
+    respect_ignore: bool,
+    rules: &ScanRules,
+) -> Result<Walked<FoundProject>> {
+    let mut walked = walk(base_path, max_depth, respect_ignore, rules, |dir| {
+        let detection = detect_project_with(dir.path, rules);
+        if detection.is_empty() {
+            return Visit { item: None, descend: true };
+        }
+        Visit {
+            item: Some(FoundProject {
+                name: dir.name.to_string(),
+                path: dir.path.to_path_buf(),
+                detection,
+                tags: Vec::new(),
+                name_source: NameSource::Dirname,
+            }),
+            descend: false,
+        }
+    })?;
+    walked.items.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(walked)
+}
+
+/// The workspace members of `parent` as projects named `parent/member`.
+/// Members normally go by their directory name; when two share one
+/// (`apps/core`, `libs/core`), those use their path inside the workspace.
+pub fn collect_members(parent: &FoundProject, rules: &ScanRules) -> Vec<FoundProject> {
+    let members = workspace_members(&parent.path, rules);
+    let basename = |p: &Path| p.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+
+    members
+        .iter()
+        .map(|path| {
+            let base = basename(path);
+            let shared = members.iter().filter(|m| basename(m) == base).count() > 1;
+            let member = if shared {
+                path.strip_prefix(&parent.path)
+                    .map(|rel| rel.to_string_lossy().into_owned())
+                    .unwrap_or(base)
+            } else {
+                base
+            };
+            FoundProject {
+                name: format!("{}/{}", parent.name, member),
+                path: path.clone(),
+                detection: detect_project_with(path, rules),
+                tags: parent.tags.clone(),
+                name_source: NameSource::Member,
+            }
+        })
+        .collect()
 }
 
 // =============================================================================
@@ -195,97 +307,151 @@ impl NameMatch {
     }
 }
 
+/// The directories in `index` under the root `base` whose name `matches`
+/// accepts: what [`walk_directories`] would find, without walking.
+fn indexed_directories(index: &DirIndex, base: &Path, matches: impl Fn(&str) -> bool) -> Vec<DirectoryMatch> {
+    index
+        .dirs_under(base, SEARCH_DEPTH)
+        .filter(|dir| matches(&dir.name))
+        .map(|dir| DirectoryMatch {
+            name: dir.name.clone(),
+            path: dir.path.clone(),
+        })
+        .collect()
+}
+
+/// Walks `base` for directories whose name `matches` accepts.
+fn walk_directories(
+    base: &Path,
+    respect_ignore: bool,
+    rules: &ScanRules,
+    matches: impl Fn(&str) -> bool + Sync,
+) -> Result<Walked<DirectoryMatch>> {
+    walk(base, SEARCH_DEPTH, respect_ignore, rules, |dir| {
+        // Hidden directories are only searched at the top level (`~/.dotfiles`).
+        if dir.name.starts_with('.') && dir.depth > 1 {
+            return Visit { item: None, descend: false };
+        }
+        Visit {
+            item: matches(dir.name).then(|| DirectoryMatch {
+                name: dir.name.to_string(),
+                path: dir.path.to_path_buf(),
+            }),
+            descend: true,
+        }
+    })
+}
+
 /// Walks the filesystem from `base` looking for directories whose name matches
 /// `query` under the given `mode`. Skips known build / dependency / system
-/// directories. Results are sorted by path depth (then by name length for
-/// substring mode, so closer-to-exact hits surface first) and truncated to a
-/// sane limit.
+/// directories, and with `respect_ignore` anything the ignore files along the
+/// way exclude. Results are sorted by path depth (then by name length for
+/// substring mode, so closer-to-exact hits surface first, then by path) and
+/// truncated to a sane limit.
+///
+/// When `rules` allow the directory index and it covers `base` and isn't
+/// stale, it answers the search after an incremental refresh; otherwise
+/// this walks, in parallel and bounded by the walk limits of `rules`
+/// (`stopped` says when the walk was cut short).
 pub fn search_directories(
     base: &Path,
     query: &str,
     mode: NameMatch,
-) -> Result<Vec<DirectoryMatch>, Box<dyn std::error::Error>> {
+    respect_ignore: bool,
+    rules: &ScanRules,
+) -> Result<Walked<DirectoryMatch>> {
     if !base.is_dir() {
-        return Err(format!("Search root is not a directory: {}", base.display()).into());
+        return Err(Error::InvalidPath {
+            path: base.to_path_buf(),
+            reason: "is not a directory",
+        });
     }
 
-    let mut matches = Vec::new();
     let target = query.to_lowercase();
+    // The index is built with ignore files honoured, so `--no-ignore`
+    // always walks.
+    let matches = |name: &str| mode.matches(&name.to_lowercase(), &target);
+    let index = (respect_ignore && rules.use_index).then(|| index_for(base, rules)).flatten();
+    let mut walked = match index {
+        Some(index) => Walked {
+            items: indexed_directories(&index, base, matches),
+            stopped: None,
+        },
+        None => walk_directories(base, respect_ignore, rules, matches)?,
+    };
 
-    search_recursive(base, &target, mode, 0, 6, &mut matches);
-
-    matches.sort_by(|a, b| {
+    walked.items.sort_by(|a, b| {
         let depth_a = a.path.components().count();
         let depth_b = b.path.components().count();
         depth_a
             .cmp(&depth_b)
             .then_with(|| a.name.len().cmp(&b.name.len()))
             .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+            .then_with(|| a.path.cmp(&b.path))
     });
 
-    matches.truncate(mode.result_limit());
+    walked.items.truncate(mode.result_limit());
 
-    Ok(matches)
+    Ok(walked)
 }
 
 /// Thin wrapper preserved for existing callers: exact-name filesystem search
 /// rooted at the user's home directory (used by `vcode add --find`).
-pub fn search_directory_by_name(
-    dir_name: &str,
-) -> Result<Vec<DirectoryMatch>, Box<dyn std::error::Error>> {
-    let home = dirs::home_dir().ok_or("Could not determine home directory")?;
-    search_directories(&home, dir_name, NameMatch::Exact)
+pub fn search_directory_by_name(dir_name: &str) -> Result<Walked<DirectoryMatch>> {
+    let home = dirs::home_dir().ok_or(Error::NoDir("home"))?;
+    search_directories(&home, dir_name, NameMatch::Exact, true, scan_rules())
 }
 
-fn search_recursive(
-    current_path: &Path,
-    target: &str,
-    mode: NameMatch,
-    current_depth: u32,
-    max_depth: u32,
-    matches: &mut Vec<DirectoryMatch>,
-) {
-    if current_depth > max_depth {
-        return;
-    }
-
-    let entries = match read_dir(current_path) {
-        Ok(e) => e,
-        Err(_) => return,
+/// Resolves `sub` to directories inside the project at `root`, for
+/// `project:sub` addressing.
+///
+/// An existing `root/sub` wins outright. Otherwise directories under `root`
+/// whose name contains the last segment of `sub` are fuzzy-ranked against
+/// the whole of `sub` by their path relative to `root`, best first; callers
+/// decide what to do with more than one. Absolute paths, and paths that
+/// leave `root` via `..` or a symlink, are refused. The search follows
+/// `rules` like [`search_directories`].
+pub fn resolve_subdir(root: &Path, sub: &str, rules: &ScanRules) -> Result<Vec<PathBuf>> {
+    let outside = |path: PathBuf| Error::InvalidPath {
+        path,
+        reason: "is outside the project root",
     };
+    let sub = sub.trim_end_matches('/');
+    if Path::new(sub).is_absolute() {
+        return Err(outside(PathBuf::from(sub)));
+    }
+    let root = std::fs::canonicalize(root)?;
+    if sub.is_empty() {
+        return Ok(vec![root]);
+    }
 
-    for entry in entries.flatten() {
-        let path = entry.path();
-
-        if !path.is_dir() {
-            continue;
-        }
-
-        let dir_name = match path.file_name().and_then(|n| n.to_str()) {
-            Some(name) => name,
-            None => continue,
+    let direct = root.join(sub);
+    if direct.is_dir() {
+        let resolved = std::fs::canonicalize(&direct)?;
+        return if resolved.starts_with(&root) {
+            Ok(vec![resolved])
+        } else {
+            Err(outside(resolved))
         };
-
-        if dir_name.starts_with('.') && current_depth > 0 {
-            continue;
-        }
-
-        if should_skip_dir(dir_name) {
-            continue;
-        }
-
-        let lower = dir_name.to_lowercase();
-        if mode.matches(&lower, target) {
-            matches.push(DirectoryMatch {
-                name: dir_name.to_string(),
-                path: path.clone(),
-            });
-        }
-
-        if current_depth < max_depth {
-            search_recursive(&path, target, mode, current_depth + 1, max_depth, matches);
-        }
     }
+    if Path::new(sub).components().any(|c| c == std::path::Component::ParentDir) {
+        return Err(outside(direct));
+    }
+
+    let last = sub.rsplit('/').next().unwrap_or(sub);
+    let mut ranked: Vec<(i64, PathBuf)> = search_directories(&root, last, NameMatch::Substring, true, rules)?
+        .items
+        .into_iter()
+        .filter_map(|m| {
+            let rel = m.path.strip_prefix(&root).ok()?.to_string_lossy().into_owned();
+            // Shallow paths win ties so `web` prefers `apps/web` over
+            // `apps/web/src/web`.
+            let depth = m.path.components().count() as i64;
+            fuzzy_score(sub, &rel).map(|score| (score * 16 - depth, m.path))
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    Ok(ranked.into_iter().map(|(_, path)| path).collect())
 }
 
 // =============================================================================
@@ -298,113 +464,126 @@ fn search_recursive(
 /// * `projects` - List of found projects to choose from
 ///
 /// # Returns
-/// Vector of selected projects, or error if cancelled/failed
-pub fn interactive_select_projects(
-    projects: Vec<FoundProject>,
-) -> Result<Vec<FoundProject>, Box<dyn std::error::Error>> {
+/// Vector of selected projects, or [`Error::Cancelled`] if the user backed out
+pub fn interactive_select_projects(projects: Vec<FoundProject>) -> Result<Vec<FoundProject>> {
     if projects.is_empty() {
         return Ok(vec![]);
     }
 
-    let options: Vec<String> = projects.iter().map(|p| p.display_name()).collect();
-    let defaults: Vec<bool> = vec![true; options.len()];
+    let options: Vec<String> = projects
+        .iter()
+        .map(|p| format!("{}  {}", p.display_name(), format!("← {}", p.name_source).dimmed()))
+        .collect();
+    let indices = multi_select("Select projects to add (Space to toggle, Enter to confirm)", &options)?;
+    Ok(indices.into_iter().map(|i| projects[i].clone()).collect())
+}
 
-    let selections = MultiSelect::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select projects to add (Space to toggle, Enter to confirm)")
-        .items(&options)
+/// The indices of the `options` the user keeps ticked (all start ticked).
+/// Escape cancels with [`Error::Cancelled`].
+pub(super) fn multi_select(prompt: &str, options: &[String]) -> Result<Vec<usize>> {
+    let defaults: Vec<bool> = vec![true; options.len()];
+    MultiSelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(options)
         .defaults(&defaults)
-        .interact_opt()?;
-
-    match selections {
-        Some(indices) => {
-            let selected: Vec<FoundProject> =
-                indices.into_iter().map(|i| projects[i].clone()).collect();
-            Ok(selected)
-        }
-        None => Err("Selection cancelled".into()),
-    }
+        .interact_opt()?
+        .ok_or(Error::Cancelled)
 }
 
 /// Adds multiple projects to the project registry
 ///
+/// All projects are written in a single locked read-modify-write, so a large
+/// scan costs one registry rewrite and can't race another `vcode` process.
+/// Name clashes, with the registry or within the batch itself (two roots
+/// that both contain `api/`), are resolved with `on_conflict`.
+///
 /// # Arguments
 /// * `projects` - Projects to add
+/// * `on_conflict` - What to do when a name is already registered elsewhere
 ///
 /// # Returns
-/// Number of successfully added projects
-pub fn add_projects(projects: Vec<FoundProject>) -> Result<usize, Box<dyn std::error::Error>> {
+/// Number of projects newly registered (including renamed and overwritten ones)
+pub fn add_projects(
+    projects: Vec<FoundProject>,
+    on_conflict: ConflictStrategy,
+    tags: &[String],
+) -> Result<usize> {
+    update_projects(|registry| Ok(register_found(registry, &projects, on_conflict, tags)))
+}
+
+/// [`add_projects`] on a registry the caller already holds the lock for.
+pub(super) fn register_found(
+    registry: &mut HashMap<String, ProjectEntry>,
+    projects: &[FoundProject],
+    on_conflict: ConflictStrategy,
+    tags: &[String],
+) -> usize {
     let mut added_count = 0;
 
     for project in projects {
-        match set_project(&project.name, project.path.to_str().unwrap()) {
-            Ok(()) => {
+        let Some(path) = project.path.to_str() else {
+            log(
+                &format!("  ⚠ Failed to add: {} (path is not valid UTF-8)", project.name),
+                LogType::Warning,
+            );
+            continue;
+        };
+        // A rescan finds the same directory again; if an earlier conflict
+        // already registered it under another name, don't mint yet another
+        // alias for it.
+        let aliased = on_conflict != ConflictStrategy::Overwrite
+            && registry.get(&project.name).is_some_and(|e| e.path != path)
+            && registry.values().any(|e| e.path == path);
+        let insertion = if aliased {
+            Insertion::Unchanged
+        } else {
+            insert_project(registry, &project.name, path, on_conflict)
+        };
+        match &insertion {
+            Insertion::Added => {
                 added_count += 1;
                 log(&format!("  + {}", project.display_name()), LogType::Normal);
             }
-            Err(_) => {
+            Insertion::Overwrote => {
+                added_count += 1;
+                log(
+                    &format!("  ! {} (replaced existing entry)", project.display_name()),
+                    LogType::Warning,
+                );
+            }
+            Insertion::Renamed(name) => {
+                added_count += 1;
                 log(
-                    &format!("  ⚠ Failed to add: {}", project.name),
+                    &format!("  + {} as '{}' (name taken)", project.display_name(), name),
+                    LogType::Normal,
+                );
+            }
+            Insertion::Skipped => {
+                log(
+                    &format!(
+                        "  ⚠ Skipped {}: name already registered to {}",
+                        project.name, registry[&project.name].path
+                    ),
                     LogType::Warning,
                 );
             }
+            Insertion::Unchanged => {
+                log(&format!("  = {} (already registered)", project.name), LogType::Normal);
+            }
         }
-    }
-
-    Ok(added_count)
-}
-
-// =============================================================================
-// Private Implementation
-// =============================================================================
-
-/// Recursively traverses directories and collects projects
-fn traverse_and_collect(
-    current_path: &Path,
-    target_depth: u32,
-    current_depth: u32,
-    found_projects: &mut Vec<FoundProject>,
-    filter_mode: FilterMode,
-) -> Result<(), Box<dyn std::error::Error>> {
-    if current_depth > target_depth {
-        return Ok(());
-    }
-
-    for entry in read_dir(current_path)? {
-        let path = entry?.path();
-
-        if !path.is_dir() {
-            continue;
-        }
-
-        let dir_name = match path.file_name().and_then(|n| n.to_str()) {
-            Some(name) => name,
-            None => continue,
-        };
-
-        if should_skip_dir(dir_name) {
-            continue;
-        }
-
-        if current_depth == target_depth {
-            let project_type = detect_project_type(&path);
-            let should_include = match filter_mode {
-                FilterMode::Auto => project_type.is_some(),
-                FilterMode::All => true,
-            };
 
-            if should_include {
-                found_projects.push(FoundProject {
-                    name: dir_name.to_string(),
-                    path: path.clone(),
-                    project_type,
-                });
-            }
-        } else {
-            traverse_and_collect(&path, target_depth, current_depth + 1, found_projects, filter_mode)?;
+        // Stamp scan tags on whichever entry now holds this directory,
+        // including ones that were already registered. A skipped
+        // directory has no entry of its own, so it gets none.
+        if insertion != Insertion::Skipped
+            && let Some(entry) = registry.values_mut().find(|e| e.path == path)
+        {
+            entry.add_tags(tags);
+            entry.add_tags(&project.tags);
         }
     }
 
-    Ok(())
+    added_count
 }
 
 // =============================================================================
@@ -414,6 +593,7 @@ fn traverse_and_collect(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scanner::ProjectType;
     use std::fs;
     use tempfile::TempDir;
 
@@ -428,11 +608,24 @@ mod tests {
         let random_dir = temp_dir.path().join("random-folder");
         fs::create_dir(&random_dir).unwrap();
 
-        let found = scan_projects(temp_dir.path(), 1, FilterMode::Auto).unwrap();
+        let found = scan_projects(temp_dir.path(), 1, FilterMode::Auto, true, true, &ScanRules::default()).unwrap().items;
 
         assert_eq!(found.len(), 1);
         assert_eq!(found[0].name, "my-rust-project");
-        assert_eq!(found[0].project_type, Some(ProjectType::Rust));
+        assert_eq!(found[0].detection.primary(), Some(ProjectType::Rust));
+    }
+
+    #[test]
+    fn test_find_projects_at_any_depth() {
+        let temp_dir = TempDir::new().unwrap();
+        for dir in ["top", "archive/2023/old", "top/nested"] {
+            fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+            fs::write(temp_dir.path().join(dir).join("Cargo.toml"), "[package]").unwrap();
+        }
+
+        let found = find_projects(temp_dir.path(), 7, true, &ScanRules::default()).unwrap().items;
+        let names: Vec<&str> = found.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["old", "top"], "projects aren't searched inside");
     }
 
     #[test]
@@ -446,11 +639,32 @@ mod tests {
         let random_dir = temp_dir.path().join("random-folder");
         fs::create_dir(&random_dir).unwrap();
 
-        let found = scan_projects(temp_dir.path(), 1, FilterMode::All).unwrap();
+        let found = scan_projects(temp_dir.path(), 1, FilterMode::All, true, true, &ScanRules::default()).unwrap().items;
 
         assert_eq!(found.len(), 2);
     }
 
+    #[test]
+    fn test_scan_honours_ignore_files() {
+        let temp_dir = TempDir::new().unwrap();
+        for dir in ["keep", "vendored/lib", "data"] {
+            fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        }
+        fs::write(temp_dir.path().join(".vcodeignore"), "vendored/\ndata\n").unwrap();
+
+        let names = |found: Vec<FoundProject>| {
+            let mut names: Vec<String> = found.into_iter().map(|p| p.name).collect();
+            names.sort();
+            names
+        };
+        let found = scan_projects(temp_dir.path(), 2, FilterMode::All, false, true, &ScanRules::default()).unwrap().items;
+        assert!(names(found).is_empty(), "keep/ has no children and the rest is ignored");
+        let found = scan_projects(temp_dir.path(), 1, FilterMode::All, false, true, &ScanRules::default()).unwrap().items;
+        assert_eq!(names(found), ["keep"]);
+        let found = scan_projects(temp_dir.path(), 2, FilterMode::All, false, false, &ScanRules::default()).unwrap().items;
+        assert_eq!(names(found), ["lib"]);
+    }
+
     #[test]
     fn test_skip_dirs() {
         let temp_dir = TempDir::new().unwrap();
@@ -458,17 +672,85 @@ mod tests {
         let node_modules = temp_dir.path().join("node_modules");
         fs::create_dir(&node_modules).unwrap();
 
-        let found = scan_projects(temp_dir.path(), 1, FilterMode::All).unwrap();
+        let found = scan_projects(temp_dir.path(), 1, FilterMode::All, true, true, &ScanRules::default()).unwrap().items;
 
         assert_eq!(found.len(), 0);
     }
 
+    #[test]
+    fn test_scan_lists_workspace_members() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("repo");
+        for member in ["crates/core", "crates/cli", "tools/core"] {
+            fs::create_dir_all(repo.join(member)).unwrap();
+            fs::write(repo.join(member).join("Cargo.toml"), "[package]").unwrap();
+        }
+        fs::write(
+            repo.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\", \"tools/core\"]\n",
+        )
+        .unwrap();
+
+        let found = scan_projects(temp_dir.path(), 1, FilterMode::Auto, true, true, &ScanRules::default()).unwrap().items;
+        let mut names: Vec<&str> = found.iter().map(|p| p.name.as_str()).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["repo", "repo/cli", "repo/crates/core", "repo/tools/core"]
+        );
+
+        let found = scan_projects(temp_dir.path(), 1, FilterMode::Auto, false, true, &ScanRules::default()).unwrap().items;
+        assert_eq!(found.len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_subdir() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("mono");
+        fs::create_dir_all(root.join("apps/web/src")).unwrap();
+        fs::create_dir_all(root.join("apps/admin")).unwrap();
+        let root = fs::canonicalize(&root).unwrap();
+        let rules = ScanRules::default();
+
+        assert_eq!(resolve_subdir(&root, "apps/web", &rules).unwrap(), [root.join("apps/web")]);
+        assert_eq!(resolve_subdir(&root, "web", &rules).unwrap()[0], root.join("apps/web"));
+        assert_eq!(resolve_subdir(&root, "ap/adm", &rules).unwrap(), [root.join("apps/admin")]);
+        assert!(resolve_subdir(&root, "nothing", &rules).unwrap().is_empty());
+
+        assert!(resolve_subdir(&root, "..", &rules).is_err());
+        assert!(resolve_subdir(&root, "../../etc", &rules).is_err());
+        assert!(resolve_subdir(&root, "/etc", &rules).is_err());
+    }
+
+    #[test]
+    fn test_register_found_keeps_existing_aliases() {
+        let entry = |path: &str| ProjectEntry::new(path.to_string());
+        let mut registry = HashMap::from([
+            ("api".to_string(), entry("/a/api")),
+            ("api-2".to_string(), entry("/b/api")),
+        ]);
+        let found = |path: &str| FoundProject {
+            name: "api".to_string(),
+            path: PathBuf::from(path),
+            detection: Detection::default(),
+            tags: Vec::new(),
+            name_source: NameSource::Dirname,
+        };
+
+        // Rescanning /b/api must not mint api-3.
+        let added = register_found(&mut registry, &[found("/b/api")], ConflictStrategy::Suffix, &[]);
+        assert_eq!((added, registry.len()), (0, 2));
+        let added = register_found(&mut registry, &[found("/c/api")], ConflictStrategy::Suffix, &[]);
+        assert_eq!((added, registry["api-3"].path.as_str()), (1, "/c/api"));
+    }
+
     #[test]
     fn test_should_skip_dir() {
-        assert!(should_skip_dir("node_modules"));
-        assert!(should_skip_dir("target"));
-        assert!(should_skip_dir(".git"));
-        assert!(!should_skip_dir("my-project"));
+        let rules = ScanRules::default();
+        assert!(rules.skips("node_modules"));
+        assert!(rules.skips("target"));
+        assert!(rules.skips(".git"));
+        assert!(!rules.skips("my-project"));
     }
 
     #[test]
@@ -481,7 +763,7 @@ mod tests {
     }
 
     #[test]
-    fn test_search_recursive_substring() {
+    fn test_search_directories_substring() {
         let temp_dir = TempDir::new().unwrap();
         let a = temp_dir.path().join("vcode-cli");
         let b = temp_dir.path().join("nested").join("my-vcode-thing");
@@ -490,8 +772,7 @@ mod tests {
         fs::create_dir_all(&b).unwrap();
         fs::create_dir_all(&c).unwrap();
 
-        let mut found = Vec::new();
-        search_recursive(temp_dir.path(), "vcode", NameMatch::Substring, 0, 4, &mut found);
+        let found = search_directories(temp_dir.path(), "vcode", NameMatch::Substring, false, &ScanRules::default()).unwrap().items;
 
         let names: Vec<&str> = found.iter().map(|m| m.name.as_str()).collect();
         assert!(names.contains(&"vcode-cli"));
@@ -500,15 +781,39 @@ mod tests {
     }
 
     #[test]
-    fn test_search_recursive_exact_rejects_substring() {
+    fn test_search_directories_exact_rejects_substring() {
         let temp_dir = TempDir::new().unwrap();
         fs::create_dir(temp_dir.path().join("vcode-cli")).unwrap();
         fs::create_dir(temp_dir.path().join("vcode")).unwrap();
 
-        let mut found = Vec::new();
-        search_recursive(temp_dir.path(), "vcode", NameMatch::Exact, 0, 2, &mut found);
+        let found = search_directories(temp_dir.path(), "vcode", NameMatch::Exact, false, &ScanRules::default()).unwrap().items;
 
         assert_eq!(found.len(), 1);
         assert_eq!(found[0].name, "vcode");
     }
+
+    #[test]
+    fn test_index_and_walk_find_the_same_directories() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path();
+        let projects = home.join("projects");
+        for dir in ["projects/.foo/inner", "projects/x/1/2/3/4/5/6/7", "projects/api/.cache", ".dots"] {
+            fs::create_dir_all(home.join(dir)).unwrap();
+        }
+        // A nested root gets its own tree, counted from where a search starts.
+        let rules = ScanRules::default().unlimited();
+        let index = DirIndex::build(&[home.to_path_buf(), projects.clone()], &rules);
+
+        for base in [home, projects.as_path()] {
+            let paths = |mut found: Vec<DirectoryMatch>| {
+                found.sort_by(|a, b| a.path.cmp(&b.path));
+                found.into_iter().map(|d| d.path).collect::<Vec<_>>()
+            };
+            let indexed = paths(indexed_directories(&index, base, |_| true));
+            let walked = paths(walk_directories(base, true, &rules, |_| true).unwrap().items);
+            assert_eq!(indexed, walked, "from {}", base.display());
+        }
+        let from_projects = indexed_directories(&index, &projects, |name| name == ".foo");
+        assert_eq!(from_projects.len(), 1, "hidden directories count from the search base");
+    }
 }
@@ -0,0 +1,402 @@
+//! Reconciling the registry with the filesystem
+//!
+//! `vcode scan --sync` compares what a scan of each project root found with
+//! what's registered: directories nobody registered are to be added,
+//! registered ones that are gone are to be removed, and a gone one that
+//! reappears elsewhere (same git remote, manifest name or directory name)
+//! is to be moved rather than removed and re-added, so it keeps its
+//! history, tags and notes. `vcode repair` uses the same matching.
+
+use super::manifest::{package_name, short_package_name};
+use super::scanner::{FoundProject, multi_select, register_found};
+use crate::core::git::{origin_url, same_remote};
+use crate::core::project::{ConflictStrategy, ProjectEntry, update_projects};
+use crate::error::Result;
+use crate::ui::logger::{LogType, log};
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// The changes that bring the registry in line with one root.
+#[derive(Debug, Clone)]
+pub struct SyncPlan {
+    pub root: PathBuf,
+    /// The walk stopped early, so there may be more to add.
+    pub partial: bool,
+    pub add: Vec<FoundProject>,
+    pub moved: Vec<SyncMove>,
+    pub missing: Vec<SyncMissing>,
+}
+
+/// A registered project whose directory is gone, found again at `to`.
+#[derive(Debug, Clone)]
+pub struct SyncMove {
+    pub name: String,
+    pub from: String,
+    pub to: FoundProject,
+    pub matched_by: MoveMatch,
+}
+
+/// Why a found directory is taken to be a missing project, strongest
+/// evidence first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MoveMatch {
+    /// Same git remote as recorded for the project.
+    GitRemote,
+    /// Its manifest's package name is the project's name.
+    Manifest,
+    /// Same directory name.
+    Name,
+}
+
+impl MoveMatch {
+    pub const ALL: [MoveMatch; 3] = [MoveMatch::GitRemote, MoveMatch::Manifest, MoveMatch::Name];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            MoveMatch::GitRemote => "git-remote",
+            MoveMatch::Manifest => "manifest",
+            MoveMatch::Name => "name",
+        }
+    }
+
+    /// Why, in words, for review lists.
+    pub fn reason(self) -> &'static str {
+        match self {
+            MoveMatch::GitRemote => "same git remote",
+            MoveMatch::Manifest => "same package name",
+            MoveMatch::Name => "same directory name",
+        }
+    }
+
+    /// Whether `dir`, recognised by `identity`, matches this way the
+    /// project `name` that was registered as `entry`.
+    pub fn matches(self, name: &str, entry: &ProjectEntry, dir: &Path, identity: &Identity) -> bool {
+        match self {
+            MoveMatch::GitRemote => identity
+                .remote
+                .as_deref()
+                .zip(entry.remote.as_deref())
+                .is_some_and(|(a, b)| same_remote(a, b)),
+            // Registered names can carry a root prefix (`work/api`) and
+            // package names a scope or module path; compare what's after.
+            MoveMatch::Manifest => identity
+                .package
+                .as_deref()
+                .is_some_and(|p| short_package_name(p) == last_segment(name)),
+            MoveMatch::Name => Path::new(&entry.path).file_name() == dir.file_name(),
+        }
+    }
+}
+
+fn last_segment(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
+}
+
+/// What a directory can be recognised by after a move.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Identity {
+    pub remote: Option<String>,
+    pub package: Option<String>,
+}
+
+impl Identity {
+    pub fn of(dir: &Path) -> Self {
+        Self {
+            remote: origin_url(dir),
+            package: package_name(dir),
+        }
+    }
+}
+
+/// The strongest way `dir` matches the missing project `name`, if any.
+pub fn best_match(name: &str, entry: &ProjectEntry, dir: &Path, identity: &Identity) -> Option<MoveMatch> {
+    MoveMatch::ALL
+        .into_iter()
+        .find(|by| by.matches(name, entry, dir, identity))
+}
+
+/// A registered project under the root whose directory is gone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncMissing {
+    pub name: String,
+    pub path: String,
+}
+
+/// What applying a plan changed.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SyncApplied {
+    pub added: usize,
+    pub moved: usize,
+    pub removed: usize,
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.add.is_empty() && self.moved.is_empty() && self.missing.is_empty()
+    }
+
+    /// The plan as printed by `--dry-run`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "root": self.root,
+            "partial": self.partial,
+            "add": self.add.iter().map(|p| json!({
+                "name": p.name,
+                "path": p.path,
+                "type": p.detection.label(),
+            })).collect::<Vec<_>>(),
+            "move": self.moved.iter().map(|m| json!({
+                "name": m.name,
+                "from": m.from,
+                "to": m.to.path,
+                "matched_by": m.matched_by.as_str(),
+            })).collect::<Vec<_>>(),
+            "remove": self.missing.iter().map(|m| json!({
+                "name": m.name,
+                "path": m.path,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    /// One line per change, for the review list.
+    fn labels(&self) -> Vec<String> {
+        let adds = self.add.iter().map(|p| format!("+ {}", p.display_name()));
+        let moves = self.moved.iter().map(|m| {
+            format!("→ {}: {} → {} ({})", m.name, m.from, m.to.path.display(), m.matched_by.reason())
+        });
+        let removals = self.missing.iter().map(|m| format!("- {} (gone from {})", m.name, m.path));
+        adds.chain(moves).chain(removals).collect()
+    }
+
+    /// Keeps the changes whose positions in [`labels`](Self::labels) are in
+    /// `keep`.
+    fn retain_indices(&mut self, keep: &HashSet<usize>) {
+        let mut i = 0..;
+        let mut kept = || i.next().is_some_and(|i| keep.contains(&i));
+        self.add.retain(|_| kept());
+        self.moved.retain(|_| kept());
+        self.missing.retain(|_| kept());
+    }
+}
+
+/// Plans the sync of each scanned root against `registry`. `scans` pairs
+/// each root with what a scan of it found and whether that walk was cut
+/// short.
+///
+/// Missing projects anywhere in the registry can be matched as moved, so
+/// a project moved from one root to another, or into a root from outside,
+/// is followed. Only missing projects under a root are planned for removal
+/// though; the rest are `vcode prune`'s business.
+pub fn plan_sync(
+    scans: Vec<(PathBuf, Vec<FoundProject>, bool)>,
+    registry: &HashMap<String, ProjectEntry>,
+) -> Vec<SyncPlan> {
+    let registered: HashSet<&Path> = registry.values().map(|e| Path::new(&e.path)).collect();
+    let mut missing: Vec<(&String, &ProjectEntry)> = registry
+        .iter()
+        .filter(|(_, e)| !Path::new(&e.path).is_dir())
+        .collect();
+    missing.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut plans: Vec<SyncPlan> = scans
+        .into_iter()
+        .map(|(root, found, partial)| SyncPlan {
+            add: found
+                .into_iter()
+                .filter(|p| !registered.contains(p.path.as_path()))
+                .collect(),
+            root,
+            partial,
+            moved: Vec::new(),
+            missing: Vec::new(),
+        })
+        .collect();
+
+    // Stronger evidence wins: every remote match is settled before any
+    // manifest match, and those before any name match.
+    let mut identities: HashMap<PathBuf, Identity> = HashMap::new();
+    let mut claimed: HashSet<&str> = HashSet::new();
+    for matched_by in MoveMatch::ALL {
+        for plan in &mut plans {
+            let mut unmatched = Vec::new();
+            for project in std::mem::take(&mut plan.add) {
+                let identity = identities
+                    .entry(project.path.clone())
+                    .or_insert_with(|| Identity::of(&project.path));
+                let found = missing.iter().find(|(name, entry)| {
+                    !claimed.contains(name.as_str()) && matched_by.matches(name, entry, &project.path, identity)
+                });
+                match found {
+                    Some((name, entry)) => {
+                        claimed.insert(name.as_str());
+                        plan.moved.push(SyncMove {
+                            name: name.to_string(),
+                            from: entry.path.clone(),
+                            to: project,
+                            matched_by,
+                        });
+                    }
+                    None => unmatched.push(project),
+                }
+            }
+            plan.add = unmatched;
+        }
+    }
+
+    // Claiming removals too keeps nested roots from both listing one.
+    for plan in &mut plans {
+        for (name, entry) in &missing {
+            if Path::new(&entry.path).starts_with(&plan.root) && claimed.insert(name.as_str()) {
+                plan.missing.push(SyncMissing {
+                    name: name.to_string(),
+                    path: entry.path.clone(),
+                });
+            }
+        }
+    }
+    plans
+}
+
+/// Lets the user untick changes they don't want, across all plans.
+pub fn select_sync_changes(mut plans: Vec<SyncPlan>) -> Result<Vec<SyncPlan>> {
+    let labels: Vec<Vec<String>> = plans.iter().map(SyncPlan::labels).collect();
+    let flat: Vec<String> = labels.concat();
+    if flat.is_empty() {
+        return Ok(plans);
+    }
+    let picked: HashSet<usize> = multi_select("Select changes to apply (Space to toggle, Enter to confirm)", &flat)?
+        .into_iter()
+        .collect();
+
+    let mut offset = 0;
+    for (plan, labels) in plans.iter_mut().zip(&labels) {
+        let keep = picked
+            .iter()
+            .filter(|&&i| i >= offset && i < offset + labels.len())
+            .map(|i| i - offset)
+            .collect();
+        plan.retain_indices(&keep);
+        offset += labels.len();
+    }
+    Ok(plans)
+}
+
+/// Applies `plans` to the registry in one locked update. Moves and removals
+/// are only carried out for entries that still point at the directory the
+/// plan saw gone, so a concurrent `vcode update` isn't undone. Entries under
+/// the synced roots that have no recorded remote get one.
+pub fn apply_sync(plans: &[SyncPlan], on_conflict: ConflictStrategy, tags: &[String]) -> Result<SyncApplied> {
+    update_projects(|registry| {
+        let mut applied = SyncApplied::default();
+        let still_gone = |entry: Option<&ProjectEntry>, path: &str| {
+            entry.is_some_and(|e| e.path == path && !Path::new(path).is_dir())
+        };
+
+        for plan in plans {
+            for m in &plan.moved {
+                if !still_gone(registry.get(&m.name), &m.from) {
+                    continue;
+                }
+                let Some(to) = m.to.path.to_str() else { continue };
+                if let Some(entry) = registry.get_mut(&m.name) {
+                    entry.move_to(to.to_string());
+                    applied.moved += 1;
+                    log(&format!("  → {} moved to {}", m.name, to), LogType::Normal);
+                }
+            }
+            for m in &plan.missing {
+                if still_gone(registry.get(&m.name), &m.path) {
+                    registry.remove(&m.name);
+                    applied.removed += 1;
+                    log(&format!("  - {} (was {})", m.name, m.path), LogType::Normal);
+                }
+            }
+            applied.added += register_found(registry, &plan.add, on_conflict, tags);
+
+            // Entries registered before remotes were recorded pick theirs
+            // up here, so a later move of them can be matched by it.
+            for entry in registry.values_mut() {
+                if entry.remote.is_none() && Path::new(&entry.path).starts_with(&plan.root) {
+                    entry.remote = origin_url(Path::new(&entry.path));
+                }
+            }
+        }
+        Ok(applied)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{ScanRules, detect_project_with};
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn found(path: &Path) -> FoundProject {
+        FoundProject {
+            name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            path: path.to_path_buf(),
+            detection: detect_project_with(path, &ScanRules::default()),
+            tags: Vec::new(),
+            name_source: Default::default(),
+        }
+    }
+
+    fn entry(path: &Path, remote: Option<&str>) -> ProjectEntry {
+        ProjectEntry {
+            path: path.to_string_lossy().into_owned(),
+            remote: remote.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plan_sync_adds_moves_and_removes() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for dir in ["kept", "fresh", "renamed", "frontend", "elsewhere/api"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("frontend/package.json"), r#"{ "name": "@acme/portal" }"#).unwrap();
+        fs::create_dir(root.join("renamed/.git")).unwrap();
+        fs::write(
+            root.join("renamed/.git/config"),
+            "[remote \"origin\"]\n\turl = https://example.com/acme/web.git\n",
+        )
+        .unwrap();
+
+        let registry = HashMap::from([
+            ("kept".to_string(), entry(&root.join("kept"), None)),
+            ("web".to_string(), entry(&root.join("web"), Some("git@example.com:acme/web.git"))),
+            ("api".to_string(), entry(&root.join("api"), None)),
+            ("work/portal".to_string(), entry(&root.join("portal"), None)),
+            ("gone".to_string(), entry(&root.join("gone"), None)),
+        ]);
+        let scan = ["kept", "fresh", "renamed", "frontend", "elsewhere/api"]
+            .iter()
+            .map(|d| found(&root.join(d)))
+            .collect();
+
+        let plans = plan_sync(vec![(root.to_path_buf(), scan, false)], &registry);
+        let plan = &plans[0];
+        let added: Vec<&str> = plan.add.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(added, ["fresh"]);
+        let moved: Vec<(&str, MoveMatch)> = plan.moved.iter().map(|m| (m.name.as_str(), m.matched_by)).collect();
+        assert_eq!(
+            moved,
+            [("web", MoveMatch::GitRemote), ("work/portal", MoveMatch::Manifest), ("api", MoveMatch::Name)]
+        );
+        assert_eq!(
+            plan.missing,
+            [SyncMissing {
+                name: "gone".to_string(),
+                path: root.join("gone").to_string_lossy().into_owned(),
+            }]
+        );
+
+        let mut picked = plan.clone();
+        picked.retain_indices(&HashSet::from([0, 4]));
+        assert_eq!((picked.add.len(), picked.moved.len(), picked.missing.len()), (1, 0, 1));
+    }
+}
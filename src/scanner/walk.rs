@@ -0,0 +1,386 @@
+//! Parallel, bounded directory walker
+//!
+//! Scan and filesystem search both walk directory trees that can be huge
+//! (`add --find` starts at `$HOME`). The walker spreads directories over a
+//! small pool of threads that steal work from each other, reads entry types
+//! from `read_dir` instead of stat-ing every path, follows symlinked
+//! directories that lead out of the tree at most once each, and
+//! stops early when the walk limits in its [`ScanRules`] run out.
+//!
+//! Results arrive in whatever order the threads finish; callers sort them.
+
+use super::ignores::IgnoreStack;
+use super::rules::ScanRules;
+use crate::error::Result;
+use crossbeam_deque::{Injector, Stealer, Worker};
+use crossbeam_utils::Backoff;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Upper bound on walker threads; directory reads stop scaling well past this.
+const MAX_THREADS: usize = 8;
+
+/// How long an idle worker sleeps between looks for work once spinning and
+/// yielding haven't turned any up.
+const IDLE_SLEEP: Duration = Duration::from_micros(500);
+
+/// Why a walk stopped before covering everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stopped {
+    /// Read this many directory entries.
+    Entries(usize),
+    /// Ran for this long.
+    Timeout(Duration),
+}
+
+impl fmt::Display for Stopped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stopped::Entries(n) => write!(f, "stopped after {} entries", n),
+            Stopped::Timeout(d) => write!(f, "stopped after {}s", d.as_secs()),
+        }
+    }
+}
+
+/// What a walk found, and whether it was cut short (in which case `items`
+/// is partial).
+#[derive(Debug)]
+pub struct Walked<T> {
+    pub items: Vec<T>,
+    pub stopped: Option<Stopped>,
+}
+
+/// A directory the walker reached. Children of the root have depth 1.
+#[derive(Debug)]
+pub struct WalkDir<'a> {
+    pub path: &'a Path,
+    pub name: &'a str,
+    pub depth: u32,
+}
+
+/// The visitor's verdict on a directory: something to report, and whether
+/// to walk into it.
+pub struct Visit<T> {
+    pub item: Option<T>,
+    pub descend: bool,
+}
+
+struct Job {
+    path: PathBuf,
+    /// Symlink-free path, for the loop guard.
+    real: PathBuf,
+    depth: u32,
+    ignores: IgnoreStack,
+}
+
+/// Shared state of one walk.
+struct Walk<'a, F> {
+    visit: &'a F,
+    /// Symlink-free root; links back inside it aren't followed.
+    root: PathBuf,
+    max_depth: u32,
+    rules: &'a ScanRules,
+    started: Instant,
+    entries: AtomicUsize,
+    /// Jobs queued or running. The walk is over when this reaches zero.
+    pending: AtomicUsize,
+    stop: AtomicBool,
+    stopped: OnceLock<Stopped>,
+    followed_links: Mutex<HashSet<PathBuf>>,
+}
+
+/// Walks the directories under `root` down to `max_depth`, calling `visit`
+/// on each one that isn't skipped (built-in or `rules`' skip list) or
+/// ignored (when `respect_ignore`), until `rules`' walk limits run out.
+/// Only reading `root` itself can fail; unreadable directories below it
+/// are passed over.
+pub fn walk<T, F>(root: &Path, max_depth: u32, respect_ignore: bool, rules: &ScanRules, visit: F) -> Result<Walked<T>>
+where
+    T: Send,
+    F: Fn(&WalkDir) -> Visit<T> + Sync,
+{
+    let ignores = IgnoreStack::new(root, respect_ignore, rules.global_ignore.as_deref());
+    walk_from(root, ignores, max_depth, rules, visit)
+}
+
+/// [`walk`] starting from ignore rules the caller already has for `root`,
+/// so a walk of a subtree still sees the ignore files above it.
+pub(super) fn walk_from<T, F>(
+    root: &Path,
+    ignores: IgnoreStack,
+    max_depth: u32,
+    rules: &ScanRules,
+    visit: F,
+) -> Result<Walked<T>>
+where
+    T: Send,
+    F: Fn(&WalkDir) -> Visit<T> + Sync,
+{
+    read_dir(root)?;
+    let real = std::fs::canonicalize(root)?;
+    let root_job = Job {
+        path: root.to_path_buf(),
+        real: real.clone(),
+        depth: 0,
+        ignores,
+    };
+
+    let walk = Walk {
+        visit: &visit,
+        root: real,
+        max_depth,
+        rules,
+        started: Instant::now(),
+        entries: AtomicUsize::new(0),
+        pending: AtomicUsize::new(1),
+        stop: AtomicBool::new(false),
+        stopped: OnceLock::new(),
+        followed_links: Mutex::new(HashSet::new()),
+    };
+
+    let injector = Injector::new();
+    injector.push(root_job);
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_THREADS);
+    let workers: Vec<Worker<Job>> = (0..threads).map(|_| Worker::new_lifo()).collect();
+    let stealers: Vec<Stealer<Job>> = workers.iter().map(|w| w.stealer()).collect();
+
+    let items = std::thread::scope(|scope| {
+        let handles: Vec<_> = workers
+            .into_iter()
+            .map(|local| {
+                let (walk, injector, stealers) = (&walk, &injector, &stealers);
+                scope.spawn(move || walk.run(&local, injector, stealers))
+            })
+            .collect();
+        // A worker that panicked would leave a silent hole in the results.
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    });
+
+    Ok(Walked {
+        items,
+        stopped: walk.stopped.get().copied(),
+    })
+}
+
+impl<T, F> Walk<'_, F>
+where
+    T: Send,
+    F: Fn(&WalkDir) -> Visit<T> + Sync,
+{
+    fn run(&self, local: &Worker<Job>, injector: &Injector<Job>, stealers: &[Stealer<Job>]) -> Vec<T> {
+        let mut items = Vec::new();
+        // Idle workers back off, so a narrow tree doesn't keep every thread
+        // spinning while one of them does the reading.
+        let backoff = Backoff::new();
+        loop {
+            if self.stop.load(Ordering::Relaxed) {
+                break;
+            }
+            match local.pop().or_else(|| steal(local, injector, stealers)) {
+                Some(job) => {
+                    self.process(job, local, &mut items);
+                    self.pending.fetch_sub(1, Ordering::AcqRel);
+                    backoff.reset();
+                }
+                None if self.pending.load(Ordering::Acquire) == 0 => break,
+                None if backoff.is_completed() => std::thread::sleep(IDLE_SLEEP),
+                None => backoff.snooze(),
+            }
+        }
+        items
+    }
+
+    fn process(&self, job: Job, local: &Worker<Job>, items: &mut Vec<T>) {
+        if self.over_budget() {
+            return;
+        }
+        let Ok(entries) = read_dir(&job.path) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let read = self.entries.fetch_add(1, Ordering::Relaxed) + 1;
+            if self.rules.limits.max_entries > 0 && read >= self.rules.limits.max_entries {
+                self.halt(Stopped::Entries(read));
+            }
+            if self.stop.load(Ordering::Relaxed) {
+                return;
+            }
+
+            // `file_type` comes from the directory listing on most
+            // platforms; only symlinks cost an extra stat.
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            let path = entry.path();
+            let real = if file_type.is_dir() {
+                job.real.join(name)
+            } else if file_type.is_symlink() && path.is_dir() {
+                match self.follow(&job.real, &path) {
+                    Some(real) => real,
+                    None => continue,
+                }
+            } else {
+                continue;
+            };
+
+            if self.rules.skips(name) || job.ignores.is_ignored(&path, true) {
+                continue;
+            }
+
+            let depth = job.depth + 1;
+            let visit = (self.visit)(&WalkDir {
+                path: &path,
+                name,
+                depth,
+            });
+            items.extend(visit.item);
+            if visit.descend && depth < self.max_depth {
+                let ignores = job.ignores.descend(&path);
+                self.pending.fetch_add(1, Ordering::AcqRel);
+                local.push(Job {
+                    path,
+                    real,
+                    depth,
+                    ignores,
+                });
+            }
+        }
+    }
+
+    /// The real path of the symlinked directory `link` inside `parent`, or
+    /// `None` when following it would loop back into an ancestor, land
+    /// inside the tree the walk already covers, or revisit a directory
+    /// another link already led to.
+    fn follow(&self, parent: &Path, link: &Path) -> Option<PathBuf> {
+        let target = std::fs::canonicalize(link).ok()?;
+        if parent.starts_with(&target) || target.starts_with(&self.root) {
+            return None;
+        }
+        let mut followed = self.followed_links.lock().unwrap_or_else(|e| e.into_inner());
+        followed.insert(target.clone()).then_some(target)
+    }
+
+    fn over_budget(&self) -> bool {
+        if self.stop.load(Ordering::Relaxed) {
+            return true;
+        }
+        let elapsed = self.started.elapsed();
+        if self.rules.limits.timeout_secs > 0 && elapsed.as_secs() >= self.rules.limits.timeout_secs {
+            self.halt(Stopped::Timeout(elapsed));
+            return true;
+        }
+        false
+    }
+
+    fn halt(&self, reason: Stopped) {
+        let _ = self.stopped.set(reason);
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A job from the shared queue or another thread, per the usual
+/// crossbeam-deque pattern.
+fn steal(local: &Worker<Job>, injector: &Injector<Job>, stealers: &[Stealer<Job>]) -> Option<Job> {
+    std::iter::repeat_with(|| {
+        injector
+            .steal_batch_and_pop(local)
+            .or_else(|| stealers.iter().map(|s| s.steal()).collect())
+    })
+    .find(|s| !s.is_retry())
+    .and_then(|s| s.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::WalkLimits;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn all_dirs(root: &Path, limits: WalkLimits) -> Walked<PathBuf> {
+        let rules = ScanRules {
+            limits,
+            ..Default::default()
+        };
+        walk(root, 10, false, &rules, |dir| Visit {
+            item: Some(dir.path.to_path_buf()),
+            descend: true,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_walk_finds_nested_dirs_and_skips() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for dir in ["a/b/c", "d", "node_modules/x"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("a/file"), "").unwrap();
+
+        let mut found = all_dirs(root, WalkLimits::default()).items;
+        found.sort();
+        assert_eq!(found, [root.join("a"), root.join("a/b"), root.join("a/b/c"), root.join("d")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_survives_symlink_loops() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("root");
+        let outside = temp.path().join("outside");
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::create_dir_all(root.join("other")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(root.join("a"), root.join("a/b/up")).unwrap();
+        std::os::unix::fs::symlink(root.join("other"), root.join("a/sibling")).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("a/link1")).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("a/link2")).unwrap();
+
+        let found = all_dirs(&root, WalkLimits::default()).items;
+        assert!(!found.contains(&root.join("a/b/up")), "a link to an ancestor isn't entered");
+        let other = found
+            .iter()
+            .filter(|p| p.ends_with("other") || p.ends_with("sibling"))
+            .collect::<Vec<_>>();
+        assert_eq!(other, [&root.join("other")], "a link back into the tree isn't entered");
+        let via_links = found
+            .iter()
+            .filter(|p| p.ends_with("link1") || p.ends_with("link2"))
+            .count();
+        assert_eq!(via_links, 1, "a target outside the tree is followed once");
+    }
+
+    #[test]
+    fn test_walk_entry_budget() {
+        let temp = TempDir::new().unwrap();
+        for i in 0..50 {
+            fs::create_dir_all(temp.path().join(format!("d{}/sub", i))).unwrap();
+        }
+        let limits = WalkLimits {
+            max_entries: 10,
+            timeout_secs: 0,
+        };
+        let walked = all_dirs(temp.path(), limits);
+        assert!(matches!(walked.stopped, Some(Stopped::Entries(_))));
+        assert!(walked.items.len() < 100);
+
+        let walked = all_dirs(temp.path(), WalkLimits::default());
+        assert_eq!((walked.items.len(), walked.stopped), (100, None));
+    }
+}
@@ -0,0 +1,439 @@
+//! Filesystem watching for `vcode watch` (Linux only)
+//!
+//! A thin inotify wrapper over `libc` that keeps a watch on every directory
+//! under each root down to that root's scan depth. Directories above the scan depth
+//! report subdirectories coming, going and moving; directories at the scan
+//! depth, where scans look for projects, report entries appearing in them,
+//! so a `git clone` or `cargo init` there is noticed. [`Watcher::poll`]
+//! turns the raw events into [`WatchEvent`]s, holding new directories back
+//! until they've stopped changing so a clone isn't judged half-way through.
+
+use super::detector::detect_project_with;
+use super::ignores::IgnoreStack;
+use super::naming::NameSource;
+use super::rules::ScanRules;
+use super::scanner::FoundProject;
+use super::walk::{Visit, walk_from};
+use crate::error::{Error, Result};
+use std::collections::{HashMap, HashSet};
+use std::ffi::{CString, OsStr};
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long a new directory must go without activity before it's checked
+/// for project markers.
+const SETTLE: Duration = Duration::from_secs(2);
+
+/// How long a "moved from" event waits for its "moved to" before the
+/// directory counts as gone from the tree.
+const MOVE_WINDOW: Duration = Duration::from_millis(500);
+
+/// Directories above the scan depth: their subdirectories matter.
+const PARENT_MASK: u32 = libc::IN_CREATE | libc::IN_MOVED_TO | libc::IN_MOVED_FROM | libc::IN_DELETE | libc::IN_ONLYDIR;
+
+/// Directories at the scan depth: anything appearing may be a marker.
+const PROJECT_MASK: u32 = libc::IN_CREATE | libc::IN_MOVED_TO | libc::IN_ONLYDIR;
+
+/// The root itself going away ends the watch.
+const ROOT_MASK: u32 = libc::IN_DELETE_SELF | libc::IN_MOVE_SELF;
+
+const EVENT_HEADER: usize = std::mem::size_of::<libc::inotify_event>();
+
+/// What the filesystem activity since the last poll amounts to.
+#[derive(Debug)]
+pub enum WatchEvent {
+    /// A new (or newly moved-in) directory at the scan depth that looks
+    /// like a project.
+    Project(FoundProject),
+    /// A directory moved within the watched tree.
+    Moved { from: PathBuf, to: PathBuf },
+    /// A directory was deleted or moved out of the watched tree.
+    Removed(PathBuf),
+}
+
+/// A watched directory: where it is, how far below its root, and which root.
+#[derive(Debug, Clone)]
+struct WatchedDir {
+    path: PathBuf,
+    depth: u32,
+    root: usize,
+}
+
+pub struct Watcher {
+    fd: OwnedFd,
+    /// Each root with its scan depth.
+    roots: Vec<(PathBuf, u32)>,
+    /// What counts as a project and which directories are left alone.
+    rules: ScanRules,
+    settle: Duration,
+    watches: HashMap<i32, WatchedDir>,
+    /// Directories at the scan depth to check once they settle, with the
+    /// time of their latest activity.
+    candidates: HashMap<PathBuf, Instant>,
+    /// Directories already known to be projects, reported or pre-existing.
+    known: HashSet<PathBuf>,
+    /// "Moved from" events by cookie, waiting for their other half.
+    moves: HashMap<u32, (PathBuf, Instant)>,
+}
+
+impl Watcher {
+    /// Starts watching each root down to its depth (at least one level),
+    /// skipping and detecting projects by `rules`. Projects that already
+    /// exist aren't reported.
+    pub fn new(roots: &[(PathBuf, u32)], rules: &ScanRules) -> Result<Self> {
+        if let Some((root, _)) = roots.iter().find(|(root, _)| !root.is_dir()) {
+            return Err(Error::InvalidPath {
+                path: root.clone(),
+                reason: "does not exist or is not a directory",
+            });
+        }
+        // SAFETY: plain syscall; a non-negative result is a new descriptor
+        // that `OwnedFd` takes ownership of and closes on drop.
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        let mut watcher = Self {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+            roots: roots.iter().map(|(root, depth)| (root.clone(), (*depth).max(1))).collect(),
+            // Watching has to see the whole tree, whatever the search limits.
+            rules: rules.unlimited(),
+            settle: SETTLE,
+            watches: HashMap::new(),
+            candidates: HashMap::new(),
+            known: HashSet::new(),
+            moves: HashMap::new(),
+        };
+        for i in 0..watcher.roots.len() {
+            watcher.watch_root(i, false)?;
+        }
+        Ok(watcher)
+    }
+
+    /// How many directories are being watched.
+    pub fn watch_count(&self) -> usize {
+        self.watches.len()
+    }
+
+    /// Waits up to `timeout` for activity and returns what it amounts to.
+    /// Fails only if inotify does, or when a root itself goes away.
+    pub fn poll(&mut self, timeout: Duration) -> Result<Vec<WatchEvent>> {
+        let mut events = Vec::new();
+        let mut pollfd = libc::pollfd {
+            fd: self.fd.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let millis = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+        // SAFETY: one valid `pollfd` for the duration of the call.
+        let ready = unsafe { libc::poll(&mut pollfd, 1, millis) };
+        if ready < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err.into());
+            }
+        } else if ready > 0 {
+            self.read_events(&mut events)?;
+        }
+        self.expire_moves(&mut events);
+        self.settle_candidates(&mut events);
+        Ok(events)
+    }
+
+    fn read_events(&mut self, out: &mut Vec<WatchEvent>) -> Result<()> {
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            // SAFETY: reads at most `buf.len()` bytes into `buf`.
+            let n = unsafe { libc::read(self.fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+            if n < 0 {
+                let err = io::Error::last_os_error();
+                match err.kind() {
+                    io::ErrorKind::WouldBlock => return Ok(()),
+                    io::ErrorKind::Interrupted => continue,
+                    _ => return Err(err.into()),
+                }
+            }
+            let n = n as usize;
+            if n == 0 {
+                return Ok(());
+            }
+
+            let mut offset = 0;
+            while offset + EVENT_HEADER <= n {
+                // SAFETY: the kernel wrote a whole event header here; the
+                // buffer isn't aligned for it, hence `read_unaligned`.
+                let event: libc::inotify_event =
+                    unsafe { std::ptr::read_unaligned(buf.as_ptr().add(offset).cast()) };
+                let start = offset + EVENT_HEADER;
+                let end = (start + event.len as usize).min(n);
+                let name = buf[start..end].split(|&b| b == 0).next().unwrap_or_default();
+                offset = end;
+                self.handle(event.wd, event.mask, event.cookie, OsStr::from_bytes(name), out)?;
+            }
+        }
+    }
+
+    fn handle(&mut self, wd: i32, mask: u32, cookie: u32, name: &OsStr, out: &mut Vec<WatchEvent>) -> Result<()> {
+        if mask & libc::IN_Q_OVERFLOW != 0 {
+            // Events were dropped; start over from what's on disk.
+            for i in 0..self.roots.len() {
+                self.forget(&self.roots[i].0.clone());
+                self.watch_root(i, true)?;
+            }
+            return Ok(());
+        }
+        if mask & libc::IN_IGNORED != 0 {
+            self.watches.remove(&wd);
+            return Ok(());
+        }
+        let Some(WatchedDir { path: dir, depth, root }) = self.watches.get(&wd).cloned() else {
+            return Ok(());
+        };
+        if depth == 0 && mask & ROOT_MASK != 0 {
+            return Err(Error::InvalidPath {
+                path: dir,
+                reason: "was moved or deleted while being watched",
+            });
+        }
+        let (root_path, target) = self.roots[root].clone();
+        if depth >= target {
+            if !self.known.contains(&dir) {
+                self.candidates.insert(dir, Instant::now());
+            }
+            return Ok(());
+        }
+        if mask & libc::IN_ISDIR == 0 {
+            return Ok(());
+        }
+
+        let path = dir.join(name);
+        if mask & libc::IN_MOVED_FROM != 0 {
+            self.moves.insert(cookie, (path, Instant::now()));
+            return Ok(());
+        }
+        if mask & libc::IN_DELETE != 0 {
+            self.forget(&path);
+            out.push(WatchEvent::Removed(path));
+            return Ok(());
+        }
+
+        // Created, or moved in.
+        let skipped = name.to_str().is_none_or(|name| self.rules.skips(name))
+            || self.ignores_for(&root_path, &dir).is_ignored(&path, true);
+        let moved_from = (mask & libc::IN_MOVED_TO != 0)
+            .then(|| self.moves.remove(&cookie))
+            .flatten();
+        if let Some((from, _)) = moved_from {
+            self.forget(&from);
+            out.push(if skipped {
+                WatchEvent::Removed(from)
+            } else {
+                WatchEvent::Moved {
+                    from,
+                    to: path.clone(),
+                }
+            });
+        }
+        if skipped {
+            return Ok(());
+        }
+
+        let depth = depth + 1;
+        // It may already be gone again; there's nothing to watch then.
+        if self.add_watch(&path, depth, root).is_ok() {
+            if depth == target {
+                self.candidates.insert(path.clone(), Instant::now());
+            }
+            self.watch_tree(&path, depth, root, true);
+        }
+        Ok(())
+    }
+
+    /// Moves whose other half never came left the tree.
+    fn expire_moves(&mut self, out: &mut Vec<WatchEvent>) {
+        let expired: Vec<u32> = self
+            .moves
+            .iter()
+            .filter(|(_, (_, at))| at.elapsed() >= MOVE_WINDOW)
+            .map(|(cookie, _)| *cookie)
+            .collect();
+        for cookie in expired {
+            if let Some((from, _)) = self.moves.remove(&cookie) {
+                self.forget(&from);
+                out.push(WatchEvent::Removed(from));
+            }
+        }
+    }
+
+    fn settle_candidates(&mut self, out: &mut Vec<WatchEvent>) {
+        let settled: Vec<PathBuf> = self
+            .candidates
+            .iter()
+            .filter(|(_, at)| at.elapsed() >= self.settle)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in settled {
+            self.candidates.remove(&path);
+            if !path.is_dir() {
+                continue;
+            }
+            let detection = detect_project_with(&path, &self.rules);
+            if detection.is_empty() {
+                continue;
+            }
+            self.known.insert(path.clone());
+            out.push(WatchEvent::Project(FoundProject {
+                name: path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+                path,
+                detection,
+                tags: Vec::new(),
+                name_source: NameSource::Dirname,
+            }));
+        }
+    }
+
+    fn watch_root(&mut self, root: usize, fresh: bool) -> Result<()> {
+        let path = self.roots[root].0.clone();
+        self.add_watch(&path, 0, root)?;
+        self.watch_tree(&path, 0, root, fresh);
+        Ok(())
+    }
+
+    fn add_watch(&mut self, path: &Path, depth: u32, root: usize) -> Result<()> {
+        let mut mask = if depth < self.roots[root].1 { PARENT_MASK } else { PROJECT_MASK };
+        if depth == 0 {
+            mask |= ROOT_MASK;
+        }
+        let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::InvalidPath {
+            path: path.to_path_buf(),
+            reason: "contains a NUL byte",
+        })?;
+        // SAFETY: `c_path` is a valid C string for the duration of the call.
+        let wd = unsafe { libc::inotify_add_watch(self.fd.as_raw_fd(), c_path.as_ptr(), mask) };
+        if wd < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+        self.watches.insert(
+            wd,
+            WatchedDir {
+                path: path.to_path_buf(),
+                depth,
+                root,
+            },
+        );
+        Ok(())
+    }
+
+    /// Watches the directories below `dir` (itself `depth` levels down) that
+    /// a scan would enter. Directories at the scan depth become candidates
+    /// when `fresh`; otherwise the ones that are already projects are
+    /// remembered so they aren't reported.
+    fn watch_tree(&mut self, dir: &Path, depth: u32, root: usize, fresh: bool) {
+        let (root_path, target) = self.roots[root].clone();
+        if depth >= target {
+            return;
+        }
+        let ignores = self.ignores_for(&root_path, dir);
+        let Ok(walked) = walk_from(dir, ignores, target - depth, &self.rules, |d| Visit {
+            item: Some((d.path.to_path_buf(), depth + d.depth)),
+            descend: true,
+        }) else {
+            return;
+        };
+        for (path, depth) in walked.items {
+            if self.add_watch(&path, depth, root).is_err() || depth < target {
+                continue;
+            }
+            if fresh {
+                self.candidates.insert(path, Instant::now());
+            } else if !detect_project_with(&path, &self.rules).is_empty() {
+                self.known.insert(path);
+            }
+        }
+    }
+
+    /// The ignore rules in effect for `dir` under `root`.
+    fn ignores_for(&self, root: &Path, dir: &Path) -> IgnoreStack {
+        IgnoreStack::for_dir(root, dir, true, self.rules.global_ignore.as_deref())
+    }
+
+    /// Stops watching `path` and everything below it.
+    fn forget(&mut self, path: &Path) {
+        let fd = self.fd.as_raw_fd();
+        self.watches.retain(|wd, dir| {
+            if !dir.path.starts_with(path) {
+                return true;
+            }
+            // SAFETY: plain syscall on our own descriptor. A watch the
+            // kernel already dropped just returns an error.
+            unsafe { libc::inotify_rm_watch(fd, *wd) };
+            false
+        });
+        self.candidates.retain(|dir, _| !dir.starts_with(path));
+        self.known.retain(|dir| !dir.starts_with(path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Polls until `done` accepts the events seen so far, or gives up.
+    fn poll_until(watcher: &mut Watcher, done: impl Fn(&[WatchEvent]) -> bool) -> Vec<WatchEvent> {
+        let mut seen = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while Instant::now() < deadline && !done(&seen) {
+            seen.extend(watcher.poll(Duration::from_millis(100)).unwrap());
+        }
+        seen
+    }
+
+    #[test]
+    fn test_watch_reports_new_projects() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("old")).unwrap();
+        fs::write(root.join("old/Cargo.toml"), "").unwrap();
+
+        let mut watcher = Watcher::new(&[(root.to_path_buf(), 1)], &ScanRules::default()).unwrap();
+        watcher.settle = Duration::ZERO;
+        fs::create_dir_all(root.join("fresh")).unwrap();
+        fs::write(root.join("fresh/go.mod"), "").unwrap();
+        fs::write(root.join("old/README.md"), "").unwrap();
+
+        let events = poll_until(&mut watcher, |e| !e.is_empty());
+        let found: Vec<&Path> = events
+            .iter()
+            .filter_map(|e| match e {
+                WatchEvent::Project(p) => Some(p.path.as_path()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(found, [root.join("fresh")]);
+    }
+
+    #[test]
+    fn test_watch_reports_moves_and_removals() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("group/api")).unwrap();
+        fs::create_dir_all(root.join("other")).unwrap();
+
+        let mut watcher = Watcher::new(&[(root.to_path_buf(), 2)], &ScanRules::default()).unwrap();
+        fs::rename(root.join("group"), root.join("team")).unwrap();
+        let events = poll_until(&mut watcher, |e| !e.is_empty());
+        assert!(matches!(
+            &events[..],
+            [WatchEvent::Moved { from, to }] if *from == root.join("group") && *to == root.join("team")
+        ));
+
+        fs::remove_dir_all(root.join("other")).unwrap();
+        let events = poll_until(&mut watcher, |e| !e.is_empty());
+        assert!(matches!(&events[..], [WatchEvent::Removed(p)] if *p == root.join("other")));
+    }
+}
@@ -0,0 +1,295 @@
+//! Workspace (monorepo) member discovery
+//!
+//! Reads the member lists of the workspace manifests vcode understands and
+//! expands their globs to directories:
+//! - `Cargo.toml`: `members` in the `[workspace]` table
+//! - `package.json`: `workspaces` (array, or `{ "packages": [...] }`), which
+//!   covers npm, yarn and the Nx/Turborepo layouts built on them
+//! - `pnpm-workspace.yaml`: the `packages` list
+//! - `go.work`: `use` directives
+//!
+//! The parsers are deliberately small line/token readers rather than full
+//! TOML/YAML implementations: they only need the one list from each file,
+//! and a manifest they can't read simply yields no members.
+
+use super::rules::ScanRules;
+use std::collections::BTreeSet;
+use std::fs::{read_dir, read_to_string};
+use std::path::{Path, PathBuf};
+
+/// How deep a `**` in a member pattern may descend.
+const MAX_GLOB_DEPTH: usize = 4;
+
+/// Returns the member directories declared by any workspace manifest in
+/// `root`, sorted and de-duplicated. The root itself is never a member, and
+/// wildcards don't match directories `rules` skip.
+pub fn workspace_members(root: &Path, rules: &ScanRules) -> Vec<PathBuf> {
+    let mut patterns = Vec::new();
+    if let Ok(s) = read_to_string(root.join("Cargo.toml")) {
+        patterns.extend(cargo_members(&s));
+    }
+    if let Ok(s) = read_to_string(root.join("package.json")) {
+        patterns.extend(package_json_workspaces(&s));
+    }
+    if let Ok(s) = read_to_string(root.join("pnpm-workspace.yaml")) {
+        patterns.extend(pnpm_packages(&s));
+    }
+    if let Ok(s) = read_to_string(root.join("go.work")) {
+        patterns.extend(go_work_uses(&s));
+    }
+
+    let mut members = BTreeSet::new();
+    let mut excluded = BTreeSet::new();
+    for pattern in &patterns {
+        match pattern.strip_prefix('!') {
+            Some(negated) => excluded.extend(expand_pattern(root, negated, rules)),
+            None => members.extend(expand_pattern(root, pattern, rules)),
+        }
+    }
+    members
+        .into_iter()
+        .filter(|m| m != root && !excluded.contains(m))
+        .collect()
+}
+
+/// `members = [...]` inside `[workspace]`, possibly spanning several lines.
+fn cargo_members(manifest: &str) -> Vec<String> {
+    let mut in_workspace = false;
+    let mut collecting = false;
+    let mut array = String::new();
+
+    for line in manifest.lines() {
+        let line = strip_comment(line, '#').trim();
+        if collecting {
+            array.push_str(line);
+            if line.contains(']') {
+                break;
+            }
+            continue;
+        }
+        if line.starts_with('[') {
+            in_workspace = line == "[workspace]";
+            continue;
+        }
+        if in_workspace
+            && let Some(rest) = line.strip_prefix("members")
+            && let Some(value) = rest.trim_start().strip_prefix('=')
+        {
+            array.push_str(value.trim());
+            if value.contains(']') {
+                break;
+            }
+            collecting = true;
+        }
+    }
+    quoted_strings(&array)
+}
+
+fn package_json_workspaces(manifest: &str) -> Vec<String> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(manifest) else {
+        return Vec::new();
+    };
+    let workspaces = match json.get("workspaces") {
+        Some(serde_json::Value::Object(obj)) => obj.get("packages"),
+        other => other,
+    };
+    workspaces
+        .and_then(|w| w.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+/// The `- item` entries under a top-level `packages:` key.
+fn pnpm_packages(manifest: &str) -> Vec<String> {
+    let mut in_packages = false;
+    let mut packages = Vec::new();
+    for raw in manifest.lines() {
+        let line = strip_comment(raw, '#');
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with([' ', '\t', '-']) {
+            in_packages = line.trim_end() == "packages:";
+            continue;
+        }
+        if in_packages && let Some(item) = line.trim().strip_prefix('-') {
+            packages.push(unquote(item.trim()).to_string());
+        }
+    }
+    packages
+}
+
+/// `use ./dir` lines and `use ( ... )` blocks.
+fn go_work_uses(manifest: &str) -> Vec<String> {
+    let mut in_block = false;
+    let mut uses = Vec::new();
+    for raw in manifest.lines() {
+        let line = strip_comment(raw, '/').trim();
+        if in_block {
+            if line.starts_with(')') {
+                in_block = false;
+            } else if !line.is_empty() {
+                uses.push(unquote(line).to_string());
+            }
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("use") {
+            let rest = rest.trim();
+            if rest.starts_with('(') {
+                in_block = true;
+            } else if !rest.is_empty() {
+                uses.push(unquote(rest).to_string());
+            }
+        }
+    }
+    uses
+}
+
+/// Cuts a trailing comment. For `/` this is Go's `//`. Good enough for
+/// manifests, where the marker doesn't appear inside member paths.
+pub(super) fn strip_comment(line: &str, marker: char) -> &str {
+    let needle = if marker == '/' { "//" } else { "#" };
+    line.find(needle).map_or(line, |i| &line[..i])
+}
+
+pub(super) fn unquote(s: &str) -> &str {
+    s.trim_matches(|c| c == '"' || c == '\'')
+}
+
+/// Every `"..."` or `'...'` string in `s`.
+fn quoted_strings(s: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '"' || c == '\'' {
+            let value: String = chars.by_ref().take_while(|&d| d != c).collect();
+            out.push(value);
+        }
+    }
+    out
+}
+
+/// Expands a member pattern relative to `root`. Segments may contain `*`
+/// wildcards, and a `**` segment matches any number of directories.
+fn expand_pattern(root: &Path, pattern: &str, rules: &ScanRules) -> Vec<PathBuf> {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    let segments: Vec<&str> = pattern
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != ".")
+        .collect();
+    let mut out = Vec::new();
+    expand_segments(root.to_path_buf(), &segments, 0, rules, &mut out);
+    out
+}
+
+fn expand_segments(dir: PathBuf, segments: &[&str], depth: usize, rules: &ScanRules, out: &mut Vec<PathBuf>) {
+    let Some((first, rest)) = segments.split_first() else {
+        if dir.is_dir() {
+            out.push(dir);
+        }
+        return;
+    };
+
+    if *first == "**" {
+        // Zero directories, then one more level (bounded).
+        expand_segments(dir.clone(), rest, depth, rules, out);
+        if depth < MAX_GLOB_DEPTH {
+            for child in child_dirs(&dir, rules) {
+                expand_segments(child, segments, depth + 1, rules, out);
+            }
+        }
+    } else if first.contains('*') {
+        for child in child_dirs(&dir, rules) {
+            let name = child.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if wildcard_match(first, name) {
+                expand_segments(child, rest, depth, rules, out);
+            }
+        }
+    } else if *first == ".." {
+        // Members outside the workspace root aren't sub-projects.
+    } else {
+        expand_segments(dir.join(first), rest, depth, rules, out);
+    }
+}
+
+fn child_dirs(dir: &Path, rules: &ScanRules) -> Vec<PathBuf> {
+    let Ok(entries) = read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.') && !rules.skips(&name)
+        })
+        .map(|e| e.path())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// `*` matches any run of characters within one path segment.
+pub(super) fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || name.len() < first.len() + last.len() || !name.ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_manifest_parsers() {
+        let cargo = "[package]\nmembers = [\"nope\"]\n\n[workspace]\nmembers = [\n  \"crates/*\", # all\n  'tools/cli',\n]\n";
+        assert_eq!(cargo_members(cargo), ["crates/*", "tools/cli"]);
+
+        let npm = r#"{ "workspaces": { "packages": ["apps/*", "packages/*"] } }"#;
+        assert_eq!(package_json_workspaces(npm), ["apps/*", "packages/*"]);
+
+        let pnpm = "packages:\n  - 'apps/*'\n  - \"!apps/legacy\"\ncatalog:\n  - x\n";
+        assert_eq!(pnpm_packages(pnpm), ["apps/*", "!apps/legacy"]);
+
+        let gowork = "go 1.22\n\nuse (\n\t./api // service\n\t./worker\n)\nuse ./tools\n";
+        assert_eq!(go_work_uses(gowork), ["./api", "./worker", "./tools"]);
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*", "web"));
+        assert!(wildcard_match("app-*", "app-web"));
+        assert!(wildcard_match("*-svc", "auth-svc"));
+        assert!(!wildcard_match("app-*", "lib-web"));
+    }
+
+    #[test]
+    fn test_workspace_members_expands_globs() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for dir in ["apps/web", "apps/legacy", "packages/ui", "packages/node_modules"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("package.json"), r#"{"workspaces": ["apps/*", "packages/*"]}"#).unwrap();
+        fs::write(root.join("pnpm-workspace.yaml"), "packages:\n  - '!apps/legacy'\n").unwrap();
+
+        let members = workspace_members(root, &ScanRules::default());
+        assert_eq!(
+            members,
+            vec![root.join("apps/web"), root.join("packages/ui")]
+        );
+    }
+}
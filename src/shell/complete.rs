@@ -0,0 +1,229 @@
+//! Dynamic completion
+//!
+//! clap's generated scripts only know the CLI's static shape. The wrappers
+//! appended by [`dynamic_script`] pass the command line typed so far to the
+//! hidden `vcode __complete line -- <words>`, which uses
+//! [`completion_context`] to decide whether the word under the cursor is a
+//! project, editor, tag or type. If it's none of those, `__complete` exits
+//! non-zero and the shell falls back to the static completions.
+
+use clap::{Arg, Command};
+use clap_complete::Shell;
+
+/// What the word under the cursor should be completed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Candidates {
+    Projects,
+    /// The first word: either a project to open or a subcommand.
+    ProjectsOrSubcommands,
+    Editors,
+    Tags,
+    Types,
+    Fixed(Vec<String>),
+}
+
+/// Works out what `words` (program name first, the partial word under the
+/// cursor last) is asking to complete, or `None` to defer to static
+/// completion (flags, paths, free text).
+pub fn completion_context(root: &Command, words: &[String]) -> Option<Candidates> {
+    let (current, typed) = words.split_last()?;
+
+    // Building propagates global args (`-e`) into every subcommand.
+    let mut root = root.clone();
+    root.build();
+
+    let mut cmd = &root;
+    let mut path: Vec<&str> = Vec::new();
+    let mut positionals: Vec<&str> = Vec::new();
+    let mut pending: Option<&Arg> = None;
+
+    for word in typed.iter().skip(1) {
+        if pending.take().is_some() {
+            continue;
+        }
+        if word.starts_with('-') && word.len() > 1 {
+            if !word.contains('=') {
+                pending = find_option(cmd, word).filter(|a| a.get_action().takes_values());
+            }
+            continue;
+        }
+        if positionals.is_empty()
+            && let Some(sub) = cmd.find_subcommand(word)
+        {
+            cmd = sub;
+            path.push(sub.get_name());
+            continue;
+        }
+        positionals.push(word);
+    }
+
+    if let Some(option) = pending {
+        return kind_for_arg(&path, option.get_id().as_str(), &positionals);
+    }
+    if current.starts_with('-') {
+        return None;
+    }
+
+    let args: Vec<&Arg> = cmd.get_positionals().collect();
+    let arg = args.get(positionals.len()).or_else(|| {
+        // Past the end: keep completing a trailing multi-value positional.
+        args.last()
+            .filter(|a| a.get_num_args().is_some_and(|n| n.max_values() > 1))
+    })?;
+    kind_for_arg(&path, arg.get_id().as_str(), &positionals)
+}
+
+fn find_option<'a>(cmd: &'a Command, word: &str) -> Option<&'a Arg> {
+    if let Some(long) = word.strip_prefix("--") {
+        return cmd.get_arguments().find(|a| a.get_long() == Some(long));
+    }
+    let mut shorts = word[1..].chars();
+    let short = shorts.next()?;
+    // `-rt` style bundles only take a value when the option is last.
+    if shorts.next().is_some() {
+        return None;
+    }
+    cmd.get_arguments().find(|a| a.get_short() == Some(short))
+}
+
+/// Maps an argument (by subcommand path and clap id) to its candidates.
+fn kind_for_arg(path: &[&str], id: &str, positionals: &[&str]) -> Option<Candidates> {
+    let fixed = |values: &[&str]| Some(Candidates::Fixed(values.iter().map(|v| v.to_string()).collect()));
+
+    match (path, id) {
+        (_, "editor") => Some(Candidates::Editors),
+        (_, "tag") => Some(Candidates::Tags),
+        ([], "project_name") => Some(Candidates::ProjectsOrSubcommands),
+        (["list"], "filter") => Some(Candidates::Types),
+        (["remove" | "update" | "where" | "tag" | "untag"], "name") => Some(Candidates::Projects),
+        (["rename"], "old_name") => Some(Candidates::Projects),
+        (["tag" | "untag"], "tags") => Some(Candidates::Tags),
+        (["config", "set"], "key") => fixed(&[
+            "editor",
+            "projects-root",
+            "on-conflict",
+            "naming",
+            "terminal",
+            "walk-max-entries",
+            "walk-timeout",
+        ]),
+        (["config", "set"], "value") => match positionals.first() {
+            Some(&"editor") => Some(Candidates::Editors),
+            Some(&"on-conflict") => fixed(&["skip", "overwrite", "suffix", "parent"]),
+            Some(&"naming") => fixed(&["dirname", "manifest", "git-remote", "parent/dir"]),
+            _ => None,
+        },
+        (["config", "remove"], "name") => Some(Candidates::Editors),
+        _ => None,
+    }
+}
+
+/// Appends the dynamic-completion hook to clap's static `script` for
+/// `shell`. Shells other than bash, zsh and fish get the static script.
+pub fn dynamic_script(shell: Shell, script: String) -> String {
+    match shell {
+        Shell::Bash => script + BASH_HOOK,
+        Shell::Zsh => {
+            // clap ends the zsh script by calling or registering `_vcode`;
+            // both need to go through the dynamic wrapper instead.
+            let dispatch = script
+                .rfind("if [ \"$funcstack[1]\" = \"_vcode\" ]; then")
+                .unwrap_or(script.len());
+            script[..dispatch].to_string() + ZSH_HOOK
+        }
+        Shell::Fish => script + FISH_HOOK,
+        _ => script,
+    }
+}
+
+const BASH_HOOK: &str = r#"
+_vcode_dynamic() {
+    local candidates
+    if candidates="$(command vcode __complete line -- "${COMP_WORDS[@]:0:COMP_CWORD+1}" 2>/dev/null)"; then
+        local IFS=$'\n'
+        COMPREPLY=($candidates)
+    else
+        _vcode "$@"
+    fi
+}
+
+complete -F _vcode_dynamic -o bashdefault -o default vcode
+"#;
+
+const ZSH_HOOK: &str = r#"_vcode_dynamic() {
+    local -a candidates
+    local output
+    if output="$(command vcode __complete line -- "${(@)words[1,CURRENT]}" 2>/dev/null)"; then
+        candidates=("${(@f)output}")
+        compadd -U -a candidates
+    else
+        _vcode "$@"
+    fi
+}
+
+if [ "$funcstack[1]" = "_vcode" ]; then
+    # Autoloaded from fpath: this file's `_vcode` is now the static one, so
+    # point later completions at the wrapper too.
+    compdef _vcode_dynamic vcode
+    _vcode_dynamic "$@"
+else
+    compdef _vcode_dynamic vcode
+fi
+"#;
+
+const FISH_HOOK: &str = r#"
+function __vcode_dynamic
+    command vcode __complete line -- (commandline -opc) (commandline -ct) 2>/dev/null
+end
+complete -c vcode -f -n '__vcode_dynamic >/dev/null' -a '(__vcode_dynamic)'
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    #[command(name = "vcode")]
+    struct TestCli {
+        project_name: Option<String>,
+        #[arg(short, long, global = true)]
+        editor: Option<String>,
+        #[command(subcommand)]
+        command: Option<TestCommands>,
+    }
+
+    #[derive(clap::Subcommand)]
+    enum TestCommands {
+        #[command(visible_alias = "rm")]
+        Remove { name: String },
+        Tag { name: String, tags: Vec<String> },
+        List {
+            #[arg(short = 'F', long)]
+            filter: Option<String>,
+        },
+    }
+
+    fn context(line: &str) -> Option<Candidates> {
+        use clap::CommandFactory;
+        let mut words: Vec<String> = line.split(' ').map(String::from).collect();
+        if line.ends_with(' ') {
+            words.pop();
+            words.push(String::new());
+        }
+        completion_context(&TestCli::command(), &words)
+    }
+
+    #[test]
+    fn test_completion_context() {
+        assert_eq!(context("vcode "), Some(Candidates::ProjectsOrSubcommands));
+        assert_eq!(context("vcode rm ap"), Some(Candidates::Projects));
+        assert_eq!(context("vcode -e "), Some(Candidates::Editors));
+        assert_eq!(context("vcode rm api -e c"), Some(Candidates::Editors));
+        assert_eq!(context("vcode tag api "), Some(Candidates::Tags));
+        assert_eq!(context("vcode tag api a b"), Some(Candidates::Tags));
+        assert_eq!(context("vcode list --filter r"), Some(Candidates::Types));
+        assert_eq!(context("vcode list --"), None);
+        assert_eq!(context("vcode rm api "), None);
+    }
+}
@@ -0,0 +1,40 @@
+# vcode shell integration for bash. Add to ~/.bashrc:
+#   eval "$(vcode init bash)"
+
+# `{{cmd}} <project>` changes into a project directory.
+{{cmd}}() {
+    if [ "$#" -eq 0 ]; then
+        cd ~ || return
+    elif [ "$#" -eq 1 ] && [ "$1" = "-" ]; then
+        cd - >/dev/null || return
+    else
+        local __vcode_dir
+        __vcode_dir="$(command vcode where "$@")" && cd -- "$__vcode_dir"
+    fi
+}
+
+# Lets `vcode ... --cd` change directory instead of using the clipboard.
+vcode() {
+    case " $* " in
+        *" --cd "*)
+            local __vcode_file __vcode_status
+            __vcode_file="$(mktemp "${TMPDIR:-/tmp}/vcode-cd.XXXXXX")" || return
+            VCODE_CD_FILE="$__vcode_file" command vcode "$@"
+            __vcode_status=$?
+            if [ "$__vcode_status" -eq 0 ] && [ -s "$__vcode_file" ]; then
+                cd -- "$(cat -- "$__vcode_file")" || __vcode_status=$?
+            fi
+            rm -f -- "$__vcode_file"
+            return "$__vcode_status"
+            ;;
+        *)
+            command vcode "$@"
+            ;;
+    esac
+}
+
+__vcode_complete_{{cmd}}() {
+    local IFS=$'\n'
+    COMPREPLY=($(command vcode __complete projects "${COMP_WORDS[COMP_CWORD]}" 2>/dev/null))
+}
+complete -F __vcode_complete_{{cmd}} {{cmd}}
@@ -0,0 +1,31 @@
+# vcode shell integration for fish. Add to ~/.config/fish/config.fish:
+#   vcode init fish | source
+
+# `{{cmd}} <project>` changes into a project directory.
+function {{cmd}}
+    if test (count $argv) -eq 0
+        cd ~
+    else if test (count $argv) -eq 1; and test "$argv[1]" = -
+        cd -
+    else
+        set -l dir (command vcode where $argv); or return
+        cd $dir
+    end
+end
+
+# Lets `vcode ... --cd` change directory instead of using the clipboard.
+function vcode --wraps vcode
+    if contains -- --cd $argv
+        set -l file (mktemp); or return
+        env VCODE_CD_FILE=$file vcode $argv
+        set -l ret $status
+        if test $ret -eq 0; and test -s $file
+            cd (cat $file); or set ret $status
+        end
+        rm -f $file
+        return $ret
+    end
+    command vcode $argv
+end
+
+complete -c {{cmd}} -f -a '(command vcode __complete projects (commandline -ct) 2>/dev/null)'
@@ -0,0 +1,69 @@
+//! Shell integration
+//!
+//! A child process can't change its parent shell's directory, so `vcode`
+//! alone can only print a path (`where`) or copy a `cd` to the clipboard
+//! (`--cd`). `vcode init <shell>` prints functions that close that gap:
+//!
+//! - a jump command (`vc` by default) that `cd`s into `vcode where`'s answer
+//! - a `vcode` wrapper that, for `--cd`, hands vcode a temp file through
+//!   [`CD_FILE_ENV`] and `cd`s to whatever it writes there
+//! - completion of registered project names for the jump command
+//!
+//! `complete.rs` adds dynamic completion (projects, editors, tags, types) to
+//! the `vcode completions` scripts.
+
+pub mod complete;
+
+use clap::ValueEnum;
+
+/// Set by the shell wrapper: when present, `--cd` writes the target
+/// directory to this file instead of copying `cd <path>` to the clipboard.
+pub const CD_FILE_ENV: &str = "VCODE_CD_FILE";
+
+/// The jump command's name when `--cmd` isn't given.
+pub const DEFAULT_JUMP_COMMAND: &str = "vc";
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// The integration script for `shell`, with the jump command named `cmd`.
+/// Returns `None` if `cmd` can't be used as a function name in every
+/// supported shell, or would shadow the `vcode` wrapper itself.
+pub fn init_script(shell: InitShell, cmd: &str) -> Option<String> {
+    let valid = cmd != "vcode"
+        && cmd.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && cmd
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return None;
+    }
+
+    let template = match shell {
+        InitShell::Bash => include_str!("init.bash"),
+        InitShell::Zsh => include_str!("init.zsh"),
+        InitShell::Fish => include_str!("init.fish"),
+    };
+    Some(template.replace("{{cmd}}", cmd))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init_script_names_jump_command() {
+        let script = init_script(InitShell::Bash, "j").unwrap();
+        assert!(script.contains("\nj() {"));
+        assert!(script.contains("complete -F __vcode_complete_j j"));
+        assert!(!script.contains("{{cmd}}"));
+
+        assert!(init_script(InitShell::Zsh, "vcode").is_none());
+        assert!(init_script(InitShell::Fish, "rm -rf").is_none());
+        assert!(init_script(InitShell::Fish, "1x").is_none());
+    }
+}
@@ -1,4 +1,6 @@
 use super::logger::{LogType, log};
+use crate::core::git::RepoStatus;
+use crate::core::project::{ProjectEntry, now_secs};
 use comfy_table::{Cell, Color, ContentArrangement, Table, presets::UTF8_FULL};
 use std::collections::HashMap;
 
@@ -6,20 +8,45 @@ use std::collections::HashMap;
 /// sorts by name for convenience, but the underlying renderer
 /// (`print_project_rows`) preserves input order. This keeps the display layer
 /// free of any project-type detection or scanning concerns.
-pub fn print_table(projects: &HashMap<String, String>) {
+pub fn print_table(projects: &HashMap<String, ProjectEntry>) {
     if projects.is_empty() {
         empty_message();
         return;
     }
-    let mut sorted: Vec<(String, String)> = projects
+    let mut sorted: Vec<(String, ProjectEntry)> = projects
         .iter()
         .map(|(k, v)| (k.clone(), v.clone()))
         .collect();
-    sorted.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()));
+    sorted.sort_by_key(|a| a.0.to_lowercase());
     print_project_rows(&sorted);
 }
 
-pub fn print_project_rows(rows: &[(String, String)]) {
+pub fn print_project_rows(rows: &[(String, ProjectEntry)]) {
+    paginate_project_rows(rows, None, None);
+}
+
+/// Like [`print_project_rows`], with a Type column. `types[i]` is the
+/// already-detected label for `rows[i]`.
+pub fn print_typed_project_rows(rows: &[(String, ProjectEntry)], types: &[String]) {
+    paginate_project_rows(rows, Some(types), None);
+}
+
+/// Like [`print_project_rows`], with branch, changes, upstream and last
+/// commit columns from `git[i]` (`None` for rows that aren't repositories),
+/// and a Type column when `types` is given.
+pub fn print_git_project_rows(
+    rows: &[(String, ProjectEntry)],
+    types: Option<&[String]>,
+    git: &[Option<RepoStatus>],
+) {
+    paginate_project_rows(rows, types, Some(git));
+}
+
+fn paginate_project_rows(
+    rows: &[(String, ProjectEntry)],
+    types: Option<&[String]>,
+    git: Option<&[Option<RepoStatus>]>,
+) {
     if rows.is_empty() {
         empty_message();
         return;
@@ -27,10 +54,10 @@ pub fn print_project_rows(rows: &[(String, String)]) {
 
     const PAGE_SIZE: usize = 20;
     let total_projects = rows.len();
-    let total_pages = (total_projects + PAGE_SIZE - 1) / PAGE_SIZE;
+    let total_pages = total_projects.div_ceil(PAGE_SIZE);
 
     if total_projects <= PAGE_SIZE {
-        display_project_page(rows, 0, total_projects, 1, 1);
+        display_project_page(rows, types, git, 0, total_projects, 1, 1);
         return;
     }
 
@@ -41,6 +68,8 @@ pub fn print_project_rows(rows: &[(String, String)]) {
 
         display_project_page(
             &rows[start_idx..end_idx],
+            types.map(|t| &t[start_idx..end_idx]),
+            git.map(|g| &g[start_idx..end_idx]),
             start_idx,
             total_projects,
             current_page + 1,
@@ -63,6 +92,134 @@ pub fn print_project_rows(rows: &[(String, String)]) {
     }
 }
 
+/// Renders the `vcode recent` table: most recently opened first, with how
+/// long ago and how often each project was opened. Callers pass rows already
+/// ordered and truncated; there is no pagination since `-n` bounds the size.
+pub fn print_recent_rows(rows: &[(String, ProjectEntry)], now: u64) {
+    if rows.is_empty() {
+        log(
+            "No projects opened yet. Open one with: vcode <name>",
+            LogType::Info,
+        );
+        return;
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("#").fg(Color::Cyan),
+            Cell::new("Name").fg(Color::Cyan),
+            Cell::new("Opened").fg(Color::Cyan),
+            Cell::new("Count").fg(Color::Cyan),
+            Cell::new("Path").fg(Color::Cyan),
+        ]);
+
+    for (idx, (name, entry)) in rows.iter().enumerate() {
+        let opened = entry
+            .last_opened
+            .map(|t| relative_age(now.saturating_sub(t)))
+            .unwrap_or_else(|| "never".to_string());
+        table.add_row(vec![
+            Cell::new(idx + 1).fg(Color::DarkGrey),
+            Cell::new(name).fg(Color::Green),
+            Cell::new(opened).fg(Color::Yellow),
+            Cell::new(entry.open_count).fg(Color::DarkGrey),
+            Cell::new(&entry.path).fg(Color::White),
+        ]);
+    }
+
+    println!("\n{}\n", table);
+}
+
+/// Renders the `vcode list --tags` overview: each tag with how many
+/// projects carry it, in the order given.
+pub fn print_tag_counts(counts: &[(String, usize)]) {
+    if counts.is_empty() {
+        log(
+            "No tags yet. Add one with: vcode tag <project> <tag>",
+            LogType::Info,
+        );
+        return;
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Tag").fg(Color::Cyan),
+            Cell::new("Projects").fg(Color::Cyan),
+        ]);
+
+    for (tag, count) in counts {
+        table.add_row(vec![
+            Cell::new(tag).fg(Color::Magenta),
+            Cell::new(count).fg(Color::White),
+        ]);
+    }
+
+    println!("\n{}\n", table);
+}
+
+/// Formats an age in seconds as a short human string (`5m ago`, `3d ago`).
+pub fn relative_age(secs: u64) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    const WEEK: u64 = 7 * DAY;
+    const YEAR: u64 = 365 * DAY;
+
+    if secs < MINUTE {
+        "just now".to_string()
+    } else if secs < HOUR {
+        format!("{}m ago", secs / MINUTE)
+    } else if secs < DAY {
+        format!("{}h ago", secs / HOUR)
+    } else if secs < WEEK {
+        format!("{}d ago", secs / DAY)
+    } else if secs < YEAR {
+        format!("{}w ago", secs / WEEK)
+    } else {
+        format!("{}y ago", secs / YEAR)
+    }
+}
+
+/// The Branch, Changes, Upstream and Last commit cells for one row.
+fn git_cells(status: Option<&RepoStatus>, now: u64) -> [Cell; 4] {
+    let Some(status) = status else {
+        return std::array::from_fn(|_| Cell::new("-").fg(Color::DarkGrey));
+    };
+    let branch = match &status.branch {
+        Some(branch) => Cell::new(branch).fg(Color::Blue),
+        None => Cell::new("detached").fg(Color::Yellow),
+    };
+    let changes = match status.changes {
+        0 => Cell::new("clean").fg(Color::Green),
+        n => Cell::new(format!("{} changed", n)).fg(Color::Yellow),
+    };
+    let upstream = match (&status.upstream, status.ahead, status.behind) {
+        (None, _, _) => Cell::new("none").fg(Color::DarkGrey),
+        (Some(_), 0, 0) => Cell::new("up to date").fg(Color::Green),
+        (Some(_), ahead, behind) => {
+            let mut counts = Vec::new();
+            if ahead > 0 {
+                counts.push(format!("↑{}", ahead));
+            }
+            if behind > 0 {
+                counts.push(format!("↓{}", behind));
+            }
+            Cell::new(counts.join(" ")).fg(if behind > 0 { Color::Red } else { Color::Yellow })
+        }
+    };
+    let last_commit = match status.last_commit {
+        Some(t) => Cell::new(relative_age(now.saturating_sub(t))).fg(Color::DarkGrey),
+        None => Cell::new("no commits").fg(Color::DarkGrey),
+    };
+    [branch, changes, upstream, last_commit]
+}
+
 fn empty_message() {
     log(
         "No projects found. Add one with: vcode add <name> <path>",
@@ -71,28 +228,60 @@ fn empty_message() {
 }
 
 fn display_project_page(
-    projects: &[(String, String)],
+    projects: &[(String, ProjectEntry)],
+    types: Option<&[String]>,
+    git: Option<&[Option<RepoStatus>]>,
     start_idx: usize,
     total: usize,
     current_page: usize,
     total_pages: usize,
 ) {
+    // Only spend a column on tags when something on this page has one.
+    let show_tags = projects.iter().any(|(_, e)| !e.tags.is_empty());
+
+    let mut header = vec![
+        Cell::new("#").fg(Color::Cyan),
+        Cell::new("Name").fg(Color::Cyan),
+    ];
+    if types.is_some() {
+        header.push(Cell::new("Type").fg(Color::Cyan));
+    }
+    if show_tags {
+        header.push(Cell::new("Tags").fg(Color::Cyan));
+    }
+    if git.is_some() {
+        for column in ["Branch", "Changes", "Upstream", "Last commit"] {
+            header.push(Cell::new(column).fg(Color::Cyan));
+        }
+    }
+    header.push(Cell::new("Path").fg(Color::Cyan));
+
     let mut table = Table::new();
     table
         .load_preset(UTF8_FULL)
         .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_header(vec![
-            Cell::new("#").fg(Color::Cyan),
-            Cell::new("Name").fg(Color::Cyan),
-            Cell::new("Path").fg(Color::Cyan),
-        ]);
+        .set_header(header);
 
-    for (idx, (name, path)) in projects.iter().enumerate() {
-        table.add_row(vec![
+    let now = now_secs();
+    for (idx, (name, entry)) in projects.iter().enumerate() {
+        let mut row = vec![
             Cell::new(start_idx + idx + 1).fg(Color::DarkGrey),
             Cell::new(name).fg(Color::Green),
-            Cell::new(path).fg(Color::White),
-        ]);
+        ];
+        if let Some(types) = types {
+            row.push(Cell::new(&types[idx]).fg(Color::Yellow));
+        }
+        if show_tags {
+            row.push(Cell::new(entry.tags.join(", ")).fg(Color::Magenta));
+        }
+        if let Some(git) = git {
+            row.extend(git_cells(git[idx].as_ref(), now));
+        }
+        row.push(match entry.missing_since {
+            Some(_) => Cell::new(format!("{} (missing)", entry.path)).fg(Color::Red),
+            None => Cell::new(&entry.path).fg(Color::White),
+        });
+        table.add_row(row);
     }
 
     println!("\n{}", table);
@@ -8,5 +8,8 @@ pub mod display;
 pub mod logger;
 
 // Re-export commonly used items
-pub use display::{print_project_rows, print_table};
+pub use display::{
+    print_git_project_rows, print_project_rows, print_recent_rows, print_table, print_tag_counts,
+    print_typed_project_rows, relative_age,
+};
 pub use logger::{LogType, log};